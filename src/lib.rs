@@ -0,0 +1,4 @@
+mod tests;
+pub mod utils;
+
+pub use utils::{compiler::{compile_str, CompiledObject, Options}, diagnostic::{Diagnostic, Phase, Severity}, semantic::lint::{Level, Lint, Lints}};
@@ -1,161 +1,413 @@
-use std::env;
+use std::{env, fs, io::{self, Read}, os::unix::process::ExitStatusExt, path::Path, process::{self, Command as Process}};
 
-mod tests;
-mod utils;
+use calamity_script::{utils::{linker, messages::{self, Lang, Message}, parser::Parser}, Diagnostic, Level, Lint, Lints, Phase};
 
-use utils::{elf::Elf, parser::Parser, semantic::{error::SemanticError, Semantic}, structs::types::LexerDigitalData};
+/// Коды завершения, не относящиеся к фазам, см. `Phase::exit_code`
+const EXIT_USAGE: i32 = 1;
+const EXIT_IO: i32 = 2;
 
-fn main() {
-    let (mut lexer_only, mut syntax, mut sem) = (false, false, false);
-    let args: Vec<String> = env::args().collect();
+const COMMANDS: [&str; 5] = ["lex", "parse", "check", "build", "run"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Lex,
+    Parse,
+    Check,
+    Build,
+    Run
+}
+
+impl Command {
+    fn last_phase(&self) -> Phase {
+        match self {
+            Command::Lex => Phase::Lexer,
+            Command::Parse => Phase::Syntax,
+            Command::Check => Phase::Semantic,
+            Command::Build | Command::Run => Phase::Object
+        }
+    }
+}
+
+struct Args {
+    command: Command,
+    files: Vec<String>,
+    out_path: Option<String>,
+    lex_objects: bool,
+    compact_mode: bool,
+    json: bool,
+    /// Записать объектный файл вместо исполняемого
+    emit_object: bool,
+    /// `run` собирает машинный код вместо интерпретации
+    native: bool,
+    lints: Lints,
+    /// Оптимизации `-O1`
+    optimize: bool
+}
+
+fn print_diagnostics(source: &str, diagnostics: Vec<Diagnostic>, json: bool) {
+    for diagnostic in diagnostics {
+        if json {
+            eprintln!("{}", diagnostic.to_json());
+        } else {
+            eprintln!("{}", diagnostic.render(source));
+        }
+    }
+}
+
+/// Разбирает аргументы командной строки. Без подкоманды выполняется
+/// `build`, старые флаги `-l`, `-st` и `-sem` выбирают подкоманду
+fn parse_args(args: &[String]) -> Result<Args, i32> {
+    let mut res = Args {
+        command: Command::Build,
+        files: Vec::new(),
+        out_path: None,
+        lex_objects: false,
+        compact_mode: false,
+        json: false,
+        emit_object: false,
+        native: false,
+        lints: Lints::default(),
+        optimize: false
+    };
+    let mut help = false;
+    let mut unknown = None;
     let mut i = 1;
-    let mut error = false;
-    let mut path = String::new();
-    let mut out_path = String::new();
-    let mut lex_objects = false;
-    let mut compact_mode = false;
-    let last = args.len() - 1;
+
+    let command = match args.get(1).map(|v| v.as_str()) {
+        Some("lex") => Some(Command::Lex),
+        Some("parse") => Some(Command::Parse),
+        Some("check") => Some(Command::Check),
+        Some("build") => Some(Command::Build),
+        Some("run") => Some(Command::Run),
+        // старый вызов начинается с имени файла. Слово без расширения,
+        // которого нет на диске и которое похоже на подкоманду, - опечатка,
+        // о ней сообщается после разбора `--lang`
+        Some(word) if !word.starts_with('-') && !word.contains(['.', '/'])
+            && !Path::new(word).exists() && COMMANDS.iter().any(|command| is_typo(word, command)) => {
+            unknown = Some(word);
+            i += 1;
+            None
+        },
+        _ => None
+    };
+    if let Some(command) = command {
+        res.command = command;
+        i += 1;
+    }
 
     while i < args.len() {
         match args[i].as_str() {
-            "-l" => {
-                lexer_only = true;
-                i+=1;
-            },
-            "-st" => {
-                syntax = true;
-                i+=1;
-            },
-            "-sem" => {
-                sem = true;
-                i+=1;
-            },
-            "-lo" => {
-                lex_objects = true;
-                i+=1;
-            },
-            "-c" => {
-                compact_mode = true;
-                i+=1;
+            "-l" => res.command = Command::Lex,
+            "-st" => res.command = Command::Parse,
+            "-sem" => res.command = Command::Check,
+            "-lo" => res.lex_objects = true,
+            "-c" => res.compact_mode = true,
+            "--native" => res.native = true,
+            "--deny-warnings" => res.lints.deny_warnings = true,
+            "-O0" => res.optimize = false,
+            "-O1" => res.optimize = true,
+            flag @ ("-W"|"-A"|"-D") => match args.get(i+1).map(|v| v.parse::<Lint>()) {
+                Some(Ok(lint)) => {
+                    let level = match flag {
+                        "-W" => Level::Warn,
+                        "-A" => Level::Allow,
+                        _ => Level::Deny
+                    };
+                    res.lints.set(lint, level);
+                    i+=1;
+                },
+                Some(Err(e)) => {
+                    eprintln!("{}", e);
+                    return Err(EXIT_USAGE);
+                },
+                None => {
+                    eprintln!("{}", Message::InvalidArguments);
+                    return Err(EXIT_USAGE);
+                }
             },
             "-o" => match args.get(i+1) {
                 Some(v) => {
-                    out_path = v.clone();
-                    i+=2;
+                    res.out_path = Some(v.clone());
+                    i+=1;
                 },
                 None => {
-                    println!("Не указан выходной файл");
-                    return;
+                    eprintln!("{}", Message::MissingOutputFile);
+                    return Err(EXIT_USAGE);
                 }
             },
-            "-h"|"--help" => {
-                println!("Компилятор принимает следующие аргументы, последний аргумент имя файла");
-                println!("\t-h | --help - отобразит текущее сообщение");
-                println!("\t-l          - остановиться после лексического анализа");
-                println!("\t-lo         - Выводит лексемы в виде объектов");
-                println!("\t-st         - остановиться после синтаксического анализа");
-                println!("\t-sem        - остановиться после семантического анализа");
-                println!("\t-c          - Компактный режим");
-                println!("\t-o          - имя выходного объектного файла");
-                return;
-            }
-            _ => {
-                if i == last && !args[i].starts_with("-") {
-                    path = args[i].clone();
-                    break;
-                } else {
-                    error = true;
-                    break;
+            "--lang" => match args.get(i+1).map(|v| v.parse::<Lang>()) {
+                Some(Ok(lang)) => {
+                    messages::set_lang(lang);
+                    i+=1;
+                },
+                Some(Err(e)) => {
+                    eprintln!("{}", e);
+                    return Err(EXIT_USAGE);
+                },
+                None => {
+                    eprintln!("{}", Message::InvalidArguments);
+                    return Err(EXIT_USAGE);
+                }
+            },
+            "-h"|"--help" => help = true,
+            arg if arg.starts_with("--error-format=") => match &arg["--error-format=".len()..] {
+                "human" => res.json = false,
+                "json" => res.json = true,
+                format => {
+                    eprintln!("{}", Message::UnknownErrorFormat(format.into()));
+                    return Err(EXIT_USAGE);
                 }
             },
+            arg if arg.starts_with("--emit=") => match &arg["--emit=".len()..] {
+                "exe" => res.emit_object = false,
+                "obj" => res.emit_object = true,
+                emit => {
+                    eprintln!("{}", Message::UnknownEmit(emit.into()));
+                    return Err(EXIT_USAGE);
+                }
+            },
+            // файл с именем подкоманды указывается после `--` или как `./lex`
+            "--" => {
+                res.files.extend(args[i+1..].iter().cloned());
+                break;
+            },
+            "-" => res.files.push(String::from("-")),
+            arg if arg.starts_with('-') => {
+                eprintln!("{}", Message::InvalidArguments);
+                return Err(EXIT_USAGE);
+            },
+            arg => res.files.push(arg.into())
         }
+        i+=1;
     };
-    if error {
-        println!("Неверные аргументы, доступные аргументы можно увидеть введя: --help");
-        return;
-    }
-    if path.is_empty() {
-        println!("No file specified");
-        return;
-        // path = String::from("test.cm");
-    }
-    if out_path.is_empty() {
-        out_path = path.clone() + ".o";
-    }
-    let mut parser_structure = Parser::new(path);
-    let res = parser_structure.run_lexer();
-    if res.is_ok() {
-        println!("Лексический анализ успешно выполнен.");
+
+    if help {
+        println!("{}", Message::Help);
+        return Err(0);
+    }
+    if let Some(word) = unknown {
+        eprintln!("{}", Message::UnknownCommand(word.into()));
+        return Err(EXIT_USAGE);
+    }
+    if res.files.is_empty() {
+        eprintln!("{}", Message::MissingInputFile);
+        return Err(EXIT_USAGE);
+    }
+    if res.out_path.is_some() && res.files.len() > 1 {
+        eprintln!("{}", Message::OutputForSeveralFiles);
+        return Err(EXIT_USAGE);
+    }
+    Ok(res)
+}
+
+/// Слова отличаются одной вставкой, удалением, заменой
+/// или перестановкой соседних букв
+fn is_typo(word: &str, command: &str) -> bool {
+    let (a, b): (Vec<char>, Vec<char>) = (word.chars().collect(), command.chars().collect());
+    let mut d: Vec<Vec<usize>> = (0..=a.len()).map(|i| (i..=i + b.len()).collect()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = (a[i-1] != b[j-1]) as usize;
+            d[i][j] = (d[i-1][j] + 1).min(d[i][j-1] + 1).min(d[i-1][j-1] + cost);
+            if i > 1 && j > 1 && a[i-1] == b[j-2] && a[i-2] == b[j-1] {
+                d[i][j] = d[i][j].min(d[i-2][j-2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()] <= 1
+}
+
+/// Сообщение об успешной фазе, при `run` не смешивается с выводом программы
+fn status(args: &Args, message: Message) {
+    if args.command != Command::Run {
+        println!("{}", message);
+    }
+}
+
+/// Читает исходный текст, `-` означает стандартный ввод
+fn read_source(file: &str) -> io::Result<(String, String)> {
+    if file == "-" {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source)?;
+        Ok((source, String::from("<stdin>")))
     } else {
-        return;
+        Ok((fs::read_to_string(file)?, file.to_string()))
+    }
+}
+
+/// Обрабатывает один входной файл и возвращает код завершения.
+/// Промежуточный результат печатается только для последней фазы команды
+fn process_file(file: &str, args: &Args) -> i32 {
+    let (source, path) = match read_source(file) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{}", Message::CannotOpenFile(file.into(), e.to_string()));
+            return EXIT_IO;
+        }
     };
-    let tokens = parser_structure.tokens.clone();
-    if !compact_mode {
-        for i in tokens {
-            if lex_objects {
-                println!("{:?}", i.token);
-            } else {
-                print!("{} ", i.token);
-            }
+    let dump = !args.compact_mode;
+    let last_phase = args.command.last_phase();
+    let mut parser_structure = Parser::from_source(source, path);
+    parser_structure.lints = args.lints.clone();
+    parser_structure.optimize = args.optimize;
+
+    match parser_structure.run_lexer() {
+        Ok(_) => status(args, Message::LexerDone),
+        Err(e) => {
+            print_diagnostics(&parser_structure.source, e, args.json);
+            return Phase::Lexer.exit_code();
         }
-        println!();
-        let mut identifiers = parser_structure.ident_map.clone().into_iter()
-            .map(|(name, id)| (id, name)).collect::<Vec<(u64, String)>>();
-        identifiers.sort_by(|a, b| a.0.cmp(&b.0));
-        let mut variables = parser_structure.vars.clone()
-            .into_iter().collect::<Vec<(u64, LexerDigitalData)>>();
-        variables.sort_by(|a, b| a.0.cmp(&b.0));
-        println!("|        Переменные        |           Числа          |");
-        println!("|---|----------------------|----|---------------------|");
-        for i in 0..identifiers.len().max(variables.len()) {
-            println!("|{} | {}|", match identifiers.get(i) {
-                Some((id, name)) => format!("{0: <2} | {1: <20}", id, name),
-                None => format!("{0: <2} | {1: <20}", "", "")
-            }, match variables.get(i) {
-                Some((id, value)) => format!("{0: <2} | {1: <20}", id, value.to_string()),
-                None => format!("{0: <2} | {1: <20}", "", "")
-            });
+    };
+    if last_phase == Phase::Lexer {
+        if dump {
+            print!("{}", parser_structure.token_table(args.lex_objects));
         }
+        return 0;
     }
-    if lexer_only { return }
 
-    let res = parser_structure.run_syntax(compact_mode);
-    if res.is_ok() {
-        println!("Синтаксический анализ успешно выполнен.");
-    } else {
-        return;
+    match parser_structure.run_syntax() {
+        Ok(_) => {
+            if dump && last_phase == Phase::Syntax {
+                println!("{:#?}", parser_structure.program);
+            }
+            status(args, Message::SyntaxDone);
+        },
+        Err(e) => {
+            print_diagnostics(&parser_structure.source, e, args.json);
+            if last_phase > Phase::Syntax {
+                if let Err(e) = parser_structure.run_semantic() {
+                    print_diagnostics(&parser_structure.source, e, args.json);
+                }
+            }
+            return Phase::Syntax.exit_code();
+        }
+    };
+    if last_phase == Phase::Syntax { return 0 }
+
+    if let Err(e) = parser_structure.run_semantic() {
+        print_diagnostics(&parser_structure.source, e, args.json);
+        return Phase::Semantic.exit_code();
+    }
+    // предупреждения, как и сообщения о фазах, не смешиваются с выводом программы
+    if args.command != Command::Run {
+        print_diagnostics(&parser_structure.source, std::mem::take(&mut parser_structure.warnings), args.json);
+    }
+    if dump && last_phase == Phase::Semantic {
+        println!("{:?}", parser_structure.asm);
+    };
+    status(args, Message::SemanticDone);
+    if last_phase == Phase::Semantic { return 0 }
+
+    if args.command == Command::Run && !args.native {
+        let stdout = io::stdout();
+        return match parser_structure.run_interpreter(io::stdin().lock(), stdout.lock()) {
+            Ok(_) => 0,
+            Err(e) => {
+                print_diagnostics(&parser_structure.source, e, args.json);
+                Phase::Runtime.exit_code()
+            }
+        };
+    }
+
+    let object = match parser_structure.run_elf() {
+        Ok(object) => object,
+        Err(e) => {
+            print_diagnostics(&parser_structure.source, e, args.json);
+            return Phase::Object.exit_code();
+        }
     };
-    if syntax { return }
-    let (inner_structure, vars, idents) = (parser_structure.program, parser_structure.vars, parser_structure.ident_map);
-    let mut semantic = Semantic::new(inner_structure.clone(), vars.clone(), idents.len() as u64);
-    let res = semantic.run_process();
-    if let Err(e) = res {
-        match e {
-            SemanticError::AssignError(from, to) =>
-                println!("Не удалось присвоить тип {} к {}", from, to),
-            SemanticError::InvalidOperation(t, o) =>
-                println!("Невозможно выполнить операцию {} над типом {}", o, t),
-            SemanticError::NotDefined(id) =>
-                println!("Переменная {} ещё не объявлена или не инициализирована", idents.iter().find(|(_, v)| **v==id).unwrap().0),
-            SemanticError::TypeError(s, f) =>
-                println!("Ошибка типов: невозможно выполнить операцию с {} и {}", f, s),
-            SemanticError::NotBoolean(t) =>
-                println!("В условии обнаружен недопустимый тип {}", t),
-            SemanticError::IdentifierAlreadyDeclared(id) =>
-                println!("Переменная {} уже объявлена", idents.iter().find(|(_, v)| **v==id).unwrap().0)
+    if args.command == Command::Run {
+        return run_program(&parser_structure, &object, args.json);
+    }
+    if args.emit_object {
+        let out_path = match (&args.out_path, file) {
+            (Some(v), _) => v.clone(),
+            (None, "-") => String::from("a.o"),
+            (None, file) => file.to_string() + ".o"
+        };
+        if let Err(e) = fs::write(&out_path, object) {
+            eprintln!("{}", Message::CannotWriteObject(e.to_string()));
+            return EXIT_IO;
         }
-        return
+        println!("{}", Message::ObjectDone);
+        return 0;
     }
-    if !compact_mode {
-        println!("{:?}", semantic.asm);
+
+    let out_path = match (&args.out_path, file) {
+        (Some(v), _) => v.clone(),
+        (None, "-") => String::from("a.out"),
+        (None, file) => match Path::new(file).extension() {
+            Some(_) => Path::new(file).with_extension("").display().to_string(),
+            None => file.to_string() + ".out"
+        }
     };
-    if res.is_ok() {
-        println!("Семантический анализ успешно выполнен.");
+    if let Err(e) = parser_structure.run_link(&object, &out_path) {
+        print_diagnostics(&parser_structure.source, e, args.json);
+        return Phase::Link.exit_code();
+    }
+    println!("{}", Message::ExecutableDone);
+    0
+}
+
+/// Собирает программу во временный исполняемый файл и запускает её,
+/// код завершения программы становится кодом завершения компилятора.
+/// Ошибки выполнения программа сама завершает кодом `Phase::Runtime`,
+/// завершение сигналом, например при переполнении стека, считается такой же ошибкой
+fn run_program(parser: &Parser, object: &[u8], json: bool) -> i32 {
+    let program = linker::temp_path("");
+    let program = program.display().to_string();
+    if let Err(e) = parser.run_link(object, &program) {
+        print_diagnostics(&parser.source, e, json);
+        return Phase::Link.exit_code();
+    }
+    let res = match Process::new(&program).status() {
+        Ok(status) => match (status.code(), status.signal()) {
+            (Some(code), _) => code,
+            (None, signal) => {
+                eprintln!("{}", Message::KilledBySignal(signal_name(signal.unwrap_or(0))));
+                Phase::Runtime.exit_code()
+            }
+        },
+        Err(e) => {
+            eprintln!("{}", Message::RunFailed(e.to_string()));
+            EXIT_IO
+        }
     };
-    if sem { return }
-    let mut elf = Elf::new(out_path, idents.len() as u16, semantic.asm, semantic.asm_idents);
-    let res = elf.process();
-    if res.is_ok() {
-        println!("Создание объектного файла успешно выполнено.");
+    let _ = fs::remove_file(&program);
+    res
+}
+
+fn signal_name(signal: i32) -> String {
+    let name = match signal {
+        4 => "SIGILL",
+        6 => "SIGABRT",
+        7 => "SIGBUS",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        11 => "SIGSEGV",
+        13 => "SIGPIPE",
+        15 => "SIGTERM",
+        _ => return signal.to_string()
     };
+    format!("{} ({})", name, signal)
+}
+
+fn main() {
+    messages::set_lang(Lang::from_env());
+    let args: Vec<String> = env::args().collect();
+    let args = match parse_args(&args) {
+        Ok(v) => v,
+        Err(code) => process::exit(code)
+    };
+
+    // остальные файлы обрабатываются и после ошибки,
+    // код завершения берётся от первой неудачи
+    let mut code = 0;
+    for file in &args.files {
+        let res = process_file(file, &args);
+        if code == 0 {
+            code = res;
+        }
+    }
+    process::exit(code);
 }
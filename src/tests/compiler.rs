@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests {
+    use crate::{compile_str, Level, Lint, Lints, Options, Phase, Severity};
+
+    #[test]
+    fn compiles_in_memory_source() {
+        let res = compile_str(include_str!("../../examples/2.cm"), Options::default());
+        let object = res.ok().and_then(|v| v.object).unwrap();
+        assert_eq!(&object[..4], b"\x7fELF");
+    }
+
+    #[test]
+    fn stops_after_requested_phase() {
+        let options = Options { stop_after: Phase::Syntax, ..Options::default() };
+        let res = compile_str(include_str!("../../examples/2.cm"), options).ok().unwrap();
+        assert!(res.program.is_some());
+        assert!(res.text.is_none());
+    }
+
+    #[test]
+    fn reports_integer_literal_overflow() {
+        let diagnostics = compile_str("{ var a: integer;;\n  a = 99999999999999999999; }", Options::default()).err().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].phase, diagnostics[0].code), (Phase::Lexer, "E0003"));
+        assert_eq!(diagnostics[0].span.map(|v| (v.line, v.column)), Some((2, 7)));
+    }
+
+    #[test]
+    fn reports_semantic_errors() {
+        let res = compile_str("{ var a: integer;; a = b; }", Options::default());
+        let diagnostics = res.err().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].phase, Phase::Semantic);
+    }
+
+    #[test]
+    fn recovers_after_syntax_errors() {
+        let source = "{\n    var a, b: integer;;\n    a = 1 +;\n    b = 2\n    output(a b);\n    if a > then output(a) end_else;\n    b = true;\n}";
+        let diagnostics = compile_str(source, Options::default()).err().unwrap();
+        let phases: Vec<Phase> = diagnostics.iter().map(|d| d.phase).collect();
+        assert_eq!(phases, vec![Phase::Syntax, Phase::Syntax, Phase::Syntax, Phase::Semantic]);
+        let lines: Vec<u32> = diagnostics.iter().map(|d| d.span.unwrap().line).collect();
+        assert_eq!(lines, vec![3, 5, 6, 7]);
+    }
+
+    #[test]
+    fn collects_several_semantic_errors() {
+        let source = "{ var a: integer; c: boolean;; a = c; output(x); c = 1; }";
+        let diagnostics = compile_str(source, Options::default()).err().unwrap();
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics.iter().all(|d| d.phase == Phase::Semantic));
+    }
+
+    #[test]
+    fn rejects_break_outside_loop() {
+        let source = "{ var a: integer;; do while true { break } loop; continue; }";
+        let diagnostics = compile_str(source, Options::default()).err().unwrap();
+        let codes: Vec<&str> = diagnostics.iter().map(|d| d.code).collect();
+        assert_eq!(codes, vec!["E0207"]);
+    }
+
+    #[test]
+    fn limits_array_storage() {
+        let diagnostics = compile_str("{ var a: array[300000000] of integer;; }", Options::default()).err().unwrap();
+        assert_eq!((diagnostics[0].phase, diagnostics[0].code), (Phase::Syntax, "E0102"));
+        let source = "{ var a: array[200000000] of integer;\n  b: array[200000000] of real;; }";
+        let diagnostics = compile_str(source, Options::default()).err().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "E0221");
+        assert_eq!(diagnostics[0].span.map(|v| (v.line, v.column)), Some((2, 3)));
+        // место соседних блоков общее, вложенных - суммируется
+        let source = "{ proc p() {\n  { var a: array[200000000] of integer;; a[0] = 1 };\n  { var b: array[200000000] of integer;; b[0] = 1;\n    { var c: array[200000000] of integer;; c[0] = 1 } };\n}; p(); }";
+        let diagnostics = compile_str(source, Options::default()).err().unwrap();
+        assert_eq!(diagnostics.iter().map(|d| (d.code, d.span.unwrap().line)).collect::<Vec<_>>(), vec![("E0221", 4)]);
+        let diagnostics = compile_str("{ proc p() { var a: array[268435455] of integer;; a[0] = 1; }; p(); }", Options::default()).err().unwrap();
+        assert_eq!(diagnostics[0].code, "E0221");
+        // массивы в `.bss` не увеличивают объектный файл
+        let object = compile_str("{ var a: array[200000000] of integer;; a[1] = 2; }", Options::default())
+            .ok().and_then(|v| v.object).unwrap();
+        assert!(object.len() < 4096);
+    }
+
+    #[test]
+    fn lint_levels() {
+        let source = "{ var a, b: integer;; a = 1; }";
+        let warnings = compile_str(source, Options::default()).ok().unwrap().warnings;
+        let codes: Vec<&str> = warnings.iter().map(|d| d.code).collect();
+        assert_eq!(codes, vec!["W0001", "W0002"]);
+        assert!(warnings.iter().all(|d| d.severity == Severity::Warning));
+
+        let mut lints = Lints::default();
+        lints.set(Lint::UnusedVariable, Level::Allow);
+        lints.set(Lint::UnusedAssignment, Level::Deny);
+        let diagnostics = compile_str(source, Options { lints, ..Options::default() }).err().unwrap();
+        let codes: Vec<&str> = diagnostics.iter().map(|d| d.code).collect();
+        assert_eq!(codes, vec!["W0002"]);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn reports_each_unused_assignment() {
+        let source = "{ var x, y, i: integer;;\n\
+            proc show() { output(y); };\n\
+            x = 1;\n\
+            x = 2;\n\
+            output(x);\n\
+            y = 0;\n\
+            for (i = 0; i < 3; i = i + 1) y = y + i;\n\
+            show();\n\
+            if x > 1 then y = 5 else y = 6 end_else;\n\
+            x = y;\n\
+            y = 7;\n\
+            show();\n\
+            x = 3;\n\
+            }";
+        let warnings = compile_str(source, Options::default()).ok().unwrap().warnings;
+        let lines: Vec<(&str, u32)> = warnings.iter().map(|d| (d.code, d.span.unwrap().line)).collect();
+        assert_eq!(lines, vec![("W0002", 3), ("W0002", 10), ("W0002", 13)]);
+    }
+
+    #[test]
+    fn deny_warnings_keeps_allowed_lints() {
+        let source = "{ var a, b: integer;; a = 1; }";
+        let mut lints = Lints::default();
+        lints.deny_warnings = true;
+        lints.set(Lint::UnusedAssignment, Level::Allow);
+        let diagnostics = compile_str(source, Options { lints, ..Options::default() }).err().unwrap();
+        let codes: Vec<&str> = diagnostics.iter().map(|d| d.code).collect();
+        assert_eq!(codes, vec!["W0001"]);
+    }
+
+    #[test]
+    fn folds_constants_and_dead_branches() {
+        let text = |source: &str, optimize: bool| {
+            let options = Options { optimize, ..Options::default() };
+            compile_str(source, options).ok().and_then(|v| v.text).unwrap()
+        };
+        let source = "{ var n: integer; b: boolean;; input(n b);\n\
+            n = 2 * 4 * 3 + n;\n\
+            if 1 == 1 then output(n) else output(0) end_else;\n\
+            do while 2 < 1 { output(n) } loop;\n\
+            output(b && true  false || !!b); }";
+        let expected = "{ var n: integer; b: boolean;; input(n b);\n\
+            n = 24 + n;\n\
+            output(n);\n\
+            output(b  b); }";
+        assert_eq!(text(source, true), text(expected, false));
+        assert!(text(source, false).len() > text(source, true).len());
+        // сравнения с NaN сворачиваются так же, как их выполняет код
+        let source = "{ const NAN = 0.0 / 0.0; output(NAN != NAN  NAN == NAN  NAN < 1.0  1.0 >= NAN); }";
+        assert_eq!(text(source, true), text("{ output(true  false  false  false); }", false));
+    }
+
+    #[test]
+    fn links_runnable_executable() {
+        use std::{io::Write, process::{Command, Stdio}};
+        use crate::utils::linker;
+
+        if Command::new("cc").arg("--version").output().is_err() {
+            return;
+        }
+        let object = compile_str(include_str!("../../examples/2.cm"), Options::default())
+            .ok().and_then(|v| v.object).unwrap();
+        let program = linker::temp_path("");
+        linker::link(&object, program.to_str().unwrap()).unwrap();
+
+        let mut child = Command::new(&program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(b"5\n").unwrap();
+        let output = child.wait_with_output().unwrap();
+        std::fs::remove_file(&program).unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "120\n");
+    }
+}
@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use crate::{compile_str, utils::structs::span::Span, Diagnostic, Options, Phase};
+
+    #[test]
+    fn renders_source_line_with_underline() {
+        let source = "{\n    var a: integer; c: real;;\n    c = 1.0;\n    a = c;\n}";
+        let diagnostics = compile_str(source, Options::default()).err().unwrap();
+        assert_eq!(diagnostics[0].span, Some(Span::new(4, 9, 4, 10).with_offsets(53, 54)));
+        assert_eq!(
+            diagnostics[0].render(source),
+            "ошибка[E0204]: Не удалось присвоить тип real к integer\n \
+             --> <input>:4:9\n  \
+             |\n\
+             4 |     a = c;\n  \
+             |     - integer\n  \
+             |         ^ real\n"
+        );
+    }
+
+    #[test]
+    fn lexer_errors_carry_spans() {
+        let diagnostics = compile_str("{\n  a = 19b;\n}", Options::default()).err().unwrap();
+        assert_eq!(diagnostics[0].span, Some(Span::new(2, 7, 2, 10).with_offsets(8, 11)));
+    }
+
+    #[test]
+    fn syntax_errors_point_at_token() {
+        let diagnostics = compile_str("{\n  var a: integer;;\n  a = 1\n  output(a);\n}", Options::default()).err().unwrap();
+        assert_eq!(diagnostics[0].span, Some(Span::new(4, 3, 4, 9).with_offsets(31, 37)));
+    }
+
+    #[test]
+    fn serializes_to_json() {
+        let diagnostics = compile_str("{\n  var a: integer;;\n  a = b;\n}", Options::default()).err().unwrap();
+        assert_eq!(
+            diagnostics[0].to_json(),
+            "{\"code\":\"E0201\",\"severity\":\"error\",\
+             \"message\":\"Переменная b ещё не объявлена или не инициализирована\",\
+             \"file\":\"<input>\",\"line\":3,\"column\":7,\
+             \"span\":{\"start\":27,\"end\":28,\"line\":3,\"column\":7,\"end_line\":3,\"end_column\":8},\
+             \"labels\":[],\"notes\":[]}"
+        );
+    }
+
+    #[test]
+    fn escapes_json_strings() {
+        let diagnostic = Diagnostic::new(Phase::Object, "a\"b", None, "line\n\tend").note("\\");
+        assert_eq!(
+            diagnostic.to_json(),
+            "{\"code\":\"\",\"severity\":\"error\",\"message\":\"line\\n\\tend\",\"file\":\"a\\\"b\",\
+             \"line\":null,\"column\":null,\"span\":null,\"labels\":[],\"notes\":[\"\\\\\"]}"
+        );
+    }
+}
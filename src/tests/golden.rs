@@ -0,0 +1,132 @@
+/// Эталонные снимки всех фаз для программ из `examples/` и `tests/`.
+/// После намеренного изменения вывода снимки обновляются командой
+/// `BLESS=1 cargo test golden`
+#[cfg(test)]
+mod tests {
+    use std::{env, fmt::Write, fs, path::{Path, PathBuf}};
+
+    use crate::{utils::parser::Parser, Severity};
+
+    const CORPUS: [&str; 2] = ["examples", "tests"];
+
+    fn root() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+    }
+
+    fn hex_dump(bytes: &[u8]) -> String {
+        let mut res = String::new();
+        for (i, line) in bytes.chunks(16).enumerate() {
+            let line: Vec<String> = line.iter().map(|b| format!("{:02x}", b)).collect();
+            writeln!(res, "{:04x}: {}", i * 16, line.join(" ")).unwrap();
+        }
+        res
+    }
+
+    /// Снимок программы: таблица лексем (`-c` и `-lo`), дерево,
+    /// диагностика и байты секции `.text`
+    fn snapshot(source: String, path: &str) -> String {
+        let mut parser = Parser::from_source(source, path);
+        let mut diagnostics = Vec::new();
+        let mut res = String::new();
+
+        let lexed = match parser.run_lexer() {
+            Ok(_) => true,
+            Err(e) => {
+                diagnostics.extend(e);
+                false
+            }
+        };
+        res += "=== tokens\n";
+        res += &parser.token_table(false);
+        res += "=== token objects\n";
+        res += &parser.token_table(true);
+
+        res += "=== ast\n";
+        if lexed {
+            let syntax = parser.run_syntax();
+            writeln!(res, "{:#?}", parser.program).unwrap();
+            if let Err(e) = syntax {
+                diagnostics.extend(e);
+            }
+            match parser.run_semantic() {
+                Ok(_) => diagnostics.extend(parser.warnings.clone()),
+                Err(e) => diagnostics.extend(e)
+            }
+        }
+
+        res += "=== diagnostics\n";
+        for diagnostic in &diagnostics {
+            writeln!(res, "{}", diagnostic.render(&parser.source)).unwrap();
+        }
+
+        res += "=== text\n";
+        if !diagnostics.iter().any(|d| d.severity == Severity::Error) {
+            res += &hex_dump(&parser.asm);
+        }
+        res
+    }
+
+    /// Первая строка, в которой снимки расходятся
+    fn first_difference(expected: &str, actual: &str) -> String {
+        let mut expected_lines = expected.lines();
+        let mut actual_lines = actual.lines();
+        for line in 1.. {
+            match (expected_lines.next(), actual_lines.next()) {
+                (Some(e), Some(a)) if e == a => continue,
+                (None, None) => break,
+                (e, a) => return format!(
+                    "line {}:\n  expected: {}\n  actual:   {}",
+                    line, e.unwrap_or("<eof>"), a.unwrap_or("<eof>")
+                )
+            }
+        }
+        String::from("line endings differ")
+    }
+
+    fn corpus() -> Vec<(String, PathBuf)> {
+        let mut res = Vec::new();
+        for dir in CORPUS {
+            let mut files: Vec<PathBuf> = fs::read_dir(root().join(dir)).unwrap()
+                .map(|entry| entry.unwrap().path())
+                .filter(|path| path.extension().is_some_and(|e| e == "cm"))
+                .collect();
+            files.sort();
+            for file in files {
+                let name = file.file_stem().unwrap().to_string_lossy();
+                res.push((format!("{}/{}", dir, file.file_name().unwrap().to_string_lossy()),
+                    root().join("tests/snapshots").join(format!("{}-{}.snap", dir, name))));
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn matches_snapshots() {
+        let bless = env::var_os("BLESS").is_some();
+        let mut failures = Vec::new();
+
+        for (path, snapshot_path) in corpus() {
+            let source = fs::read_to_string(root().join(&path)).unwrap();
+            let actual = snapshot(source, &path);
+            if bless {
+                fs::write(&snapshot_path, &actual).unwrap();
+                continue;
+            }
+            match fs::read_to_string(&snapshot_path) {
+                Ok(expected) if expected == actual => (),
+                Ok(expected) => failures.push(format!("{}: {}", path, first_difference(&expected, &actual))),
+                Err(_) => failures.push(format!("{}: missing {}", path, display(&snapshot_path)))
+            }
+        }
+
+        assert!(
+            failures.is_empty(),
+            "snapshots differ, rerun with BLESS=1 to accept the new output:\n{}",
+            failures.join("\n")
+        );
+    }
+
+    fn display(path: &Path) -> String {
+        path.strip_prefix(root()).unwrap_or(path).display().to_string()
+    }
+}
@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, process::{Command, Output, Stdio}};
+
+    use crate::{utils::{linker, parser::Parser}, Phase};
+
+    fn checked(source: &str) -> Parser {
+        compiled(source, false)
+    }
+
+    fn compiled(source: &str, optimize: bool) -> Parser {
+        let mut parser = Parser::from_source(source, "<input>");
+        parser.optimize = optimize;
+        parser.run_lexer().unwrap();
+        parser.run_syntax().unwrap();
+        parser.run_semantic().unwrap();
+        parser
+    }
+
+    fn interpret(source: &str, input: &str) -> String {
+        let mut output = Vec::new();
+        checked(source).run_interpreter(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    /// Вывод и код завершения, с которым `run` завершился бы при интерпретации
+    fn interpret_status(source: &str, input: &str) -> (String, Option<i32>) {
+        let mut output = Vec::new();
+        let code = match checked(source).run_interpreter(input.as_bytes(), &mut output) {
+            Ok(_) => 0,
+            Err(_) => Phase::Runtime.exit_code()
+        };
+        (String::from_utf8(output).unwrap(), Some(code))
+    }
+
+    fn native(source: &str, input: &str, optimize: bool) -> (String, Option<i32>) {
+        let output = execute(compiled(source, optimize), input);
+        (String::from_utf8(output.stdout).unwrap(), output.status.code())
+    }
+
+    fn run_native(source: &str, input: &str) -> Output {
+        execute(checked(source), input)
+    }
+
+    fn execute(parser: Parser, input: &str) -> Output {
+        let program = linker::temp_path("");
+        let program = program.to_str().unwrap();
+        parser.run_link(&parser.run_elf().unwrap(), program).unwrap();
+
+        let mut child = Command::new(program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        std::fs::remove_file(program).unwrap();
+        output
+    }
+
+    #[test]
+    fn runs_examples() {
+        assert_eq!(interpret(include_str!("../../examples/1.cm"), "5"), "1\n");
+        assert_eq!(interpret(include_str!("../../examples/2.cm"), "5"), "120\n");
+        assert_eq!(interpret(include_str!("../../examples/3.cm"), "1.5 2.5 0"), "2.000000\n");
+        assert_eq!(interpret(include_str!("../../examples/5.cm"), "6"), "0\n1\n1\n2\n3\n5\n");
+    }
+
+    #[test]
+    fn reads_input_like_scanf() {
+        let source = "{ var a, b: integer; c: real; d: boolean;; input(a b c d); output(a b c d); }";
+        assert_eq!(interpret(source, " -12\n+7 2.5e1 3"), "-12\n7\n25.000000\n1\n");
+        // при ошибке ввода переменные сохраняют значения
+        assert_eq!(interpret(source, "1 x"), "1\n0\n0.000000\n0\n");
+    }
+
+    #[test]
+    fn comparisons_bind_tighter_than_logic() {
+        assert_eq!(interpret(include_str!("../../tests/precedence.cm"), ""), "1\n2\n3\n1\n1\n");
+    }
+
+    #[test]
+    fn reports_division_by_zero() {
+        let mut parser = checked("{ var a: integer;;\n  a = 0;\n  a = 1 / a;\n}");
+        let diagnostics = parser.run_interpreter(&b""[..], Vec::new()).err().unwrap();
+        assert_eq!(diagnostics[0].code, "E0501");
+        assert_eq!(diagnostics[0].span.map(|v| (v.line, v.column)), Some((3, 11)));
+        parser.program.clear();
+        assert!(parser.run_interpreter(&b""[..], Vec::new()).is_ok());
+
+        // ноль в отрицательной степени тоже деление на ноль
+        let parser = checked("{ var a: integer;;\n  a = 7 mod 2 + 0 ** -1;\n}");
+        let diagnostics = parser.run_interpreter(&b""[..], Vec::new()).err().unwrap();
+        assert_eq!(diagnostics[0].span.map(|v| (v.line, v.column)), Some((2, 17)));
+
+        if Command::new("cc").arg("--version").output().is_err() {
+            return;
+        }
+        // машинный код вместо сигнала SIGFPE сообщает об ошибке сам
+        let source = "{ var a, b: integer;;\n  input(a b);\n  output(a mod b);\n}";
+        for input in ["1 0", "-9223372036854775808 -1"] {
+            let output = run_native(source, input);
+            assert_eq!(output.status.code(), Some(Phase::Runtime.exit_code()));
+            let stderr = String::from_utf8(output.stderr).unwrap();
+            assert!(stderr.starts_with("<input>:3: ") && stderr.contains("[E0501]"), "{}", stderr);
+        }
+        assert!(run_native(source, "-9223372036854775807 -1").status.success());
+    }
+
+    #[test]
+    fn reports_index_out_of_bounds() {
+        let source = "{ var i: integer; a: array[3] of integer;;\n  input(i);\n  a[i] = 1;\n}";
+        let diagnostics = checked(source).run_interpreter(&b"3"[..], Vec::new()).err().unwrap();
+        assert_eq!(diagnostics[0].code, "E0503");
+        assert_eq!(diagnostics[0].span.map(|v| (v.line, v.column)), Some((3, 3)));
+        assert!(checked(source).run_interpreter(&b"2"[..], Vec::new()).is_ok());
+
+        if Command::new("cc").arg("--version").output().is_err() {
+            return;
+        }
+        // отрицательный индекс при беззнаковом сравнении тоже за границами
+        let output = run_native(source, "-1");
+        assert_eq!(output.status.code(), Some(Phase::Runtime.exit_code()));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.starts_with("<input>:3: "), "{}", stderr);
+        assert!(stderr.contains("[E0503]") && stderr.contains("-1"), "{}", stderr);
+        assert!(run_native(source, "2").status.success());
+    }
+
+    #[test]
+    fn matches_native_code() {
+        if Command::new("cc").arg("--version").output().is_err() {
+            return;
+        }
+        let sources = [
+            (include_str!("../../examples/1.cm"), "5"),
+            (include_str!("../../examples/2.cm"), "6"),
+            (include_str!("../../examples/3.cm"), "0.5 -2.25 4 0"),
+            (include_str!("../../examples/4.cm"), "10"),
+            (include_str!("../../examples/5.cm"), "10"),
+            (include_str!("../../examples/6.cm"), ""),
+            (include_str!("../../tests/break_continue.cm"), ""),
+            (include_str!("../../tests/negation.cm"), ""),
+            (include_str!("../../tests/conversions.cm"), ""),
+            (include_str!("../../tests/functions.cm"), "5"),
+            (include_str!("../../tests/arrays.cm"), "5 9 -3 7 0 2 1"),
+            (include_str!("../../tests/strings.cm"), ""),
+            (include_str!("../../tests/arithmetic.cm"), ""),
+            (include_str!("../../tests/scopes.cm"), ""),
+            (include_str!("../../tests/constants.cm"), ""),
+            (include_str!("../../tests/for_loops.cm"), ""),
+            (include_str!("../../tests/short_circuit.cm"), ""),
+            (include_str!("../../tests/precedence.cm"), ""),
+            (include_str!("../../tests/case.cm"), ""),
+            (include_str!("../../tests/optimize.cm"), ""),
+            ("{ var a, b: integer; x: real;; input(a b x);\n\
+              output(a / b  a * b  a - b  a + b  a < b  a >= b  a == b);\n\
+              output(x / 3.0  x < 1.0  x >= 0.0  x != 2.0  !(x > 1.0) || false && true); }", "-7 2 -1.5"),
+            // сравнения с NaN ложны, кроме `!=`
+            ("{ var n, z: real;; input(z); n = z / z;\n\
+              output(n == n  n != n  n < 1.0  n > 1.0  n <= 1.0  n >= 1.0  1.0 < n  1.0 >= n  n != 1); }", "0"),
+            // то же для NaN, известного при компиляции: с `-O1` сравнения сворачиваются
+            ("{ const NAN = 0.0 / 0.0;\n\
+              output(0.0 / 0.0 != 0.0 / 0.0  NAN == NAN  NAN < 1.0  NAN >= 1.0  1.0 > NAN  NAN != 1  !(NAN <= NAN)); }", ""),
+            // ошибки выполнения: выведенное до ошибки сохраняется, код завершения общий
+            ("{ var a, b: integer;; input(a b);\n\
+              output(a); output(a div b  a mod b  a / b); }", "7 -1"),
+            ("{ var a, b: integer;; input(a b);\n\
+              output(a); output(a div b  a mod b  a / b); }", "7 0"),
+            ("{ var a, b: integer;; input(a b);\n\
+              output(a); output(a div b  a mod b  a / b); }", "-9223372036854775808 -1"),
+            ("{ var a, b: integer;; input(a b);\n\
+              output(a); output(b ** a); }", "-2 0"),
+            ("{ var i: integer; a: array[3] of integer;; input(i);\n\
+              output(i); a[i] = 1; output(a[i]); }", "3")
+        ];
+        for (source, input) in sources {
+            let expected = interpret_status(source, input);
+            assert_eq!(expected, native(source, input, false), "{}", source);
+            assert_eq!(expected, native(source, input, true), "-O1\n{}", source);
+        }
+    }
+}
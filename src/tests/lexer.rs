@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use crate::utils::{lexer::Lexer, parser::Parser, structs::tokens::{DelimitersGroup, KeywordsGroup, TokenGroupLexer}};
+
+    #[test]
+    fn iterates_until_eof() {
+        let tokens: Vec<_> = Lexer::new("{ var a: integer;; }", "<input>")
+            .map(|token| token.unwrap().0)
+            .collect();
+        assert_eq!(tokens, vec![
+            TokenGroupLexer::Delimiters(DelimitersGroup::LeftCurlyBracket),
+            TokenGroupLexer::Keywords(KeywordsGroup::Var),
+            TokenGroupLexer::Identifier("a".into()),
+            TokenGroupLexer::Delimiters(DelimitersGroup::Colon),
+            TokenGroupLexer::Keywords(KeywordsGroup::Integer),
+            TokenGroupLexer::Delimiters(DelimitersGroup::Semicolon),
+            TokenGroupLexer::Delimiters(DelimitersGroup::Semicolon),
+            TokenGroupLexer::Delimiters(DelimitersGroup::RightCurlyBracket),
+            TokenGroupLexer::Eof
+        ]);
+    }
+
+    #[test]
+    fn spans_are_byte_offsets() {
+        let source = "% комментарий %\nabc <= 17o";
+        let spans: Vec<_> = Lexer::new(source, "<input>")
+            .map(|token| token.unwrap())
+            .map(|(token, span)| (token, span.range(), span.line, span.column))
+            .collect();
+        let start = source.find("abc").unwrap();
+        assert_eq!(spans, vec![
+            (TokenGroupLexer::Identifier("abc".into()), start..start + 3, 2, 1),
+            (TokenGroupLexer::Delimiters(DelimitersGroup::LessEqual), start + 4..start + 6, 2, 5),
+            (TokenGroupLexer::Variables("17o".into()), start + 7..start + 10, 2, 8),
+            (TokenGroupLexer::Eof, source.len()..source.len(), 2, 11)
+        ]);
+    }
+
+    #[test]
+    fn reads_string_literals() {
+        let source = r#"output("a\tb \"c\"\\" "")"#;
+        let tokens: Vec<_> = Lexer::new(source, "<input>")
+            .map(|token| token.unwrap())
+            .collect();
+        assert_eq!(tokens[2].0, TokenGroupLexer::String("a\tb \"c\"\\".into()));
+        assert_eq!(tokens[2].1.range(), 7..21);
+        assert_eq!(tokens[3].0, TokenGroupLexer::String(String::new()));
+
+        let errors: Vec<_> = ["\"abc\n\"", "\"abc", "\"a\\qc\""].iter()
+            .map(|source| Lexer::new(source, "<input>").find_map(|token| token.err()).unwrap())
+            .collect();
+        assert_eq!(errors[0].span.range(), 0..4);
+        assert_eq!(errors[1].span.range(), 0..4);
+        assert_eq!((errors[2].span.range(), errors[2].span.column), (2..4, 3));
+    }
+
+    #[test]
+    fn embedded_nul_is_illegal() {
+        let tokens: Vec<_> = Lexer::new("{ }\0 }", "<input>")
+            .map(|token| token.unwrap().0)
+            .collect();
+        assert_eq!(tokens, vec![
+            TokenGroupLexer::Delimiters(DelimitersGroup::LeftCurlyBracket),
+            TokenGroupLexer::Delimiters(DelimitersGroup::RightCurlyBracket),
+            TokenGroupLexer::Illegal(('\0', 1, 4)),
+            TokenGroupLexer::Delimiters(DelimitersGroup::RightCurlyBracket),
+            TokenGroupLexer::Eof
+        ]);
+
+        let mut parser = Parser::from_source("{ output(1); }\0garbage $$$ {{{", "<input>");
+        let diagnostics = parser.run_lexer().err().unwrap();
+        assert_eq!(diagnostics[0].code, "E0001");
+        assert_eq!(diagnostics[0].span.map(|v| v.range()), Some(14..15));
+        let mut parser = Parser::from_source("{ output(\"a\0b\"); }", "<input>");
+        assert_eq!(parser.run_lexer().err().unwrap()[0].code, "E0001");
+    }
+
+    #[test]
+    fn stops_after_error() {
+        let mut lexer = Lexer::new("a = 19b; b = 1;", "<input>");
+        assert!(lexer.by_ref().any(|token| token.is_err()));
+        assert!(lexer.next().is_none());
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+    use crate::{compile_str, utils::messages::{set_lang, Lang}, Options};
+
+    #[test]
+    fn parses_lang_names() {
+        assert_eq!("ru".parse::<Lang>(), Ok(Lang::Ru));
+        assert_eq!("EN".parse::<Lang>(), Ok(Lang::En));
+        assert!("de".parse::<Lang>().is_err());
+    }
+
+    #[test]
+    fn renders_english_diagnostics() {
+        set_lang(Lang::En);
+        let source = "{\n    var a: integer;;\n    a = true;\n}";
+        let diagnostics = compile_str(source, Options::default()).err().unwrap();
+        assert_eq!(
+            diagnostics[0].render(source),
+            "error[E0204]: Cannot assign type boolean to integer\n \
+             --> <input>:3:9\n  \
+             |\n\
+             3 |     a = true;\n  \
+             |     - integer\n  \
+             |         ^^^^ boolean\n"
+        );
+        set_lang(Lang::Ru);
+    }
+
+    #[test]
+    fn translates_syntax_errors() {
+        set_lang(Lang::En);
+        let diagnostics = compile_str("{\n  var a: integer;;\n  a = 1\n  output(a);\n}", Options::default()).err().unwrap();
+        assert_eq!(diagnostics[0].labels[0].message, "Expected ';'");
+        set_lang(Lang::Ru);
+    }
+}
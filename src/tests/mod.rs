@@ -1 +1,7 @@
-pub mod tokens;
\ No newline at end of file
+pub mod tokens;
+pub mod compiler;
+pub mod diagnostic;
+pub mod lexer;
+pub mod messages;
+pub mod interpreter;
+pub mod golden;
\ No newline at end of file
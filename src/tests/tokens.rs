@@ -5,6 +5,7 @@ mod tests {
     use crate::utils::structs::tokens::DigitType;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn digit_types_ordering() {
         assert_eq!(DigitType::Binary   >> DigitType::Binary,   true);
         assert_eq!(DigitType::Binary   >> DigitType::Octal,    true);
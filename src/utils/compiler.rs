@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use super::{diagnostic::{Diagnostic, Phase}, parser::Parser, semantic::lint::Lints, structs::{program::MainOperation, tokens::Token, types::LexerDigitalData}};
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Имя файла, которое попадёт в диагностические сообщения
+    pub path: String,
+    /// Последняя выполняемая фаза компиляции
+    pub stop_after: Phase,
+    /// Уровни предупреждений семантического анализа
+    pub lints: Lints,
+    /// Свёртка констант и удаление недостижимых ветвей (`-O1`)
+    pub optimize: bool
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            path: String::from("<input>"),
+            stop_after: Phase::Object,
+            lints: Lints::default(),
+            optimize: false
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompiledObject {
+    pub tokens: Vec<Token>,
+    pub ident_map: HashMap<String, u64>,
+    pub vars: HashMap<u64, LexerDigitalData>,
+    pub program: Option<Vec<MainOperation>>,
+    pub text: Option<Vec<u8>>,
+    pub object: Option<Vec<u8>>,
+    /// Предупреждения семантического анализа
+    pub warnings: Vec<Diagnostic>
+}
+
+/// Выполняет все фазы компиляции над исходным текстом в памяти,
+/// не обращаясь к файловой системе и ничего не печатая.
+pub fn compile_str(source: &str, options: Options) -> Result<CompiledObject, Vec<Diagnostic>> {
+    let mut parser = Parser::from_source(source, options.path);
+    parser.lints = options.lints;
+    parser.optimize = options.optimize;
+    parser.run_lexer()?;
+
+    let mut result = CompiledObject {
+        tokens: parser.tokens.clone(),
+        ident_map: parser.ident_map.clone(),
+        vars: parser.vars.clone(),
+        program: None,
+        text: None,
+        object: None,
+        warnings: Vec::new()
+    };
+    if options.stop_after == Phase::Lexer {
+        return Ok(result);
+    }
+
+    if let Err(mut diagnostics) = parser.run_syntax() {
+        // частично разобранную программу всё равно проверяем семантически,
+        // чтобы за один запуск сообщить обо всех найденных ошибках
+        if options.stop_after >= Phase::Semantic {
+            if let Err(errors) = parser.run_semantic() {
+                diagnostics.extend(errors);
+            }
+        }
+        return Err(diagnostics);
+    }
+    result.program = Some(parser.program.clone());
+    if options.stop_after == Phase::Syntax {
+        return Ok(result);
+    }
+
+    parser.run_semantic()?;
+    result.text = Some(parser.asm.clone());
+    result.warnings = parser.warnings.clone();
+    if options.stop_after == Phase::Semantic {
+        return Ok(result);
+    }
+
+    result.object = Some(parser.run_elf()?);
+    Ok(result)
+}
@@ -0,0 +1,251 @@
+use std::fmt::Display;
+
+use super::{messages::Message, structs::span::Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    Lexer,
+    Syntax,
+    Semantic,
+    Object,
+    Link,
+    Runtime
+}
+
+impl Phase {
+    /// Код завершения при ошибке фазы: у каждой фазы свой, чтобы сборочные
+    /// скрипты могли отличить ошибку в программе от ошибки окружения.
+    /// Ошибка выполнения завершает с этим же кодом и машинный код
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Phase::Lexer => 3,
+            Phase::Syntax => 4,
+            Phase::Semantic => 5,
+            Phase::Object => 6,
+            Phase::Link => 7,
+            Phase::Runtime => 8
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note
+}
+
+impl Severity {
+    /// Имя уровня для машиночитаемого вывода, от языка не зависит
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note"
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "{}", Message::Error),
+            Severity::Warning => write!(f, "{}", Message::Warning),
+            Severity::Note => write!(f, "{}", Message::Note)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+    pub primary: bool
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub phase: Phase,
+    /// Постоянный код диагностики, не зависящий от текста сообщения
+    pub code: &'static str,
+    pub severity: Severity,
+    pub path: String,
+    pub span: Option<Span>,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>
+}
+
+impl Diagnostic {
+    pub fn new(phase: Phase, path: impl Into<String>, span: Option<Span>, message: impl Into<String>) -> Self {
+        Self {
+            phase,
+            code: "",
+            severity: Severity::Error,
+            path: path.into(),
+            span,
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new()
+        }
+    }
+
+    pub fn code(mut self, code: &'static str) -> Self {
+        self.code = code;
+        self
+    }
+
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Примечание, выводится после исходного текста как `= note: ...`
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Подпись под основным участком, отмечается `^^^`
+    pub fn label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label { span, message: message.into(), primary: true });
+        self
+    }
+
+    /// Подпись под дополнительным участком, отмечается `---`
+    pub fn secondary(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label { span, message: message.into(), primary: false });
+        self
+    }
+
+    /// Выводит сообщение в стиле rustc: адрес, строку исходного текста
+    /// и подчёркивание отмеченных участков
+    pub fn render(&self, source: &str) -> String {
+        let mut res = match self.code {
+            "" => format!("{}: {}\n", self.severity, self.message),
+            code => format!("{}[{}]: {}\n", self.severity, code, self.message)
+        };
+        let span = match self.span {
+            Some(span) => span,
+            None => {
+                for note in &self.notes {
+                    res += &format!("  = {}: {}\n", Message::Note, note);
+                }
+                return res;
+            }
+        };
+
+        let mut labels = self.labels.clone();
+        if !labels.iter().any(|label| label.primary) {
+            labels.push(Label { span, message: String::new(), primary: true });
+        }
+        labels.sort_by_key(|label| (label.span.line, label.span.column));
+
+        let lines: Vec<&str> = source.lines().collect();
+        let width = labels.iter().map(|label| label.span.line).max().unwrap_or(0).to_string().len();
+        let pad = " ".repeat(width);
+
+        res += &format!("{}--> {}:{}:{}\n", pad, self.path, span.line, span.column);
+        res += &format!("{} |\n", pad);
+
+        let mut last_line = None;
+        for label in labels {
+            let line = lines.get((label.span.line as usize).wrapping_sub(1)).copied().unwrap_or("");
+            if last_line != Some(label.span.line) {
+                res += &format!("{:>width$} | {}\n", label.span.line, line);
+                last_line = Some(label.span.line);
+            }
+
+            let start = label.span.column.max(1) as usize - 1;
+            let end = if label.span.end_line == label.span.line {
+                label.span.end_column.max(1) as usize - 1
+            } else {
+                line.chars().count()
+            };
+            let indent: String = line.chars().chain(std::iter::repeat(' '))
+                .take(start)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let marker = if label.primary { "^" } else { "-" };
+            let underline = marker.repeat(end.saturating_sub(start).max(1));
+
+            res += &format!("{} | {}{}", pad, indent, underline);
+            if !label.message.is_empty() {
+                res += &format!(" {}", label.message);
+            }
+            res += "\n";
+        }
+        for note in &self.notes {
+            res += &format!("{} = {}: {}\n", pad, Message::Note, note);
+        }
+
+        res
+    }
+
+    /// Один JSON-объект в строке, для CI и автоматической проверки
+    pub fn to_json(&self) -> String {
+        let position = |span: Option<Span>| match span {
+            Some(span) => (span.line.to_string(), span.column.to_string()),
+            None => ("null".into(), "null".into())
+        };
+        let (line, column) = position(self.span);
+        let span = match self.span {
+            Some(span) => json_span(&span),
+            None => "null".into()
+        };
+        let labels: Vec<String> = self.labels.iter().map(|label| format!(
+            "{{\"span\":{},\"message\":{},\"primary\":{}}}",
+            json_span(&label.span),
+            json_string(&label.message),
+            label.primary
+        )).collect();
+        let notes: Vec<String> = self.notes.iter().map(|note| json_string(note)).collect();
+
+        format!(
+            "{{\"code\":{},\"severity\":{},\"message\":{},\"file\":{},\"line\":{},\"column\":{},\"span\":{},\"labels\":[{}],\"notes\":[{}]}}",
+            json_string(self.code),
+            json_string(self.severity.as_str()),
+            json_string(&self.message),
+            json_string(&self.path),
+            line,
+            column,
+            span,
+            labels.join(","),
+            notes.join(",")
+        )
+    }
+}
+
+fn json_span(span: &Span) -> String {
+    format!(
+        "{{\"start\":{},\"end\":{},\"line\":{},\"column\":{},\"end_line\":{},\"end_column\":{}}}",
+        span.start, span.end, span.line, span.column, span.end_line, span.end_column
+    )
+}
+
+fn json_string(value: &str) -> String {
+    let mut res = String::with_capacity(value.len() + 2);
+    res.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => res += "\\\"",
+            '\\' => res += "\\\\",
+            '\n' => res += "\\n",
+            '\r' => res += "\\r",
+            '\t' => res += "\\t",
+            c if (c as u32) < 0x20 => res += &format!("\\u{:04x}", c as u32),
+            c => res.push(c)
+        }
+    }
+    res.push('"');
+    res
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "{}:{}:{}: {}", self.path, span.line, span.column, self.message),
+            None => write!(f, "{}", self.message)
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fs::{self, File}, io::{Error, Seek, SeekFrom, Write}, path::Path};
+use std::{collections::{HashMap, HashSet}, io::{Cursor, Error, Seek, SeekFrom, Write}};
 
 use rela::Rela;
 use sections::Section;
@@ -9,105 +9,154 @@ mod symtab;
 mod rela;
 
 pub struct Elf {
-    res_file: String,
     position: u64,
     ident_count: u16,
     ident_name_offset: Vec<u32>,
     program: Vec<u8>,
-    asm_idents: Vec<(u64, u64, bool)>
+    asm_idents: Vec<(u64, u64, bool)>,
+    /// Смещения ячеек переменных в `.data`
+    slots: HashMap<u64, u64>,
+    /// Смещения и длины глобальных массивов в `.bss`
+    arrays: HashMap<u64, (u64, u64)>,
+    /// Формат сообщения о выходе за границы массива для `dprintf`
+    message: Option<String>,
+    message_offset: u64,
+    /// Формат сообщения о делении на ноль для `dprintf`
+    division: Option<String>,
+    division_offset: u64,
+    dprintf_name_offset: u32,
+    pow_name_offset: u32,
+    /// Строковые литералы для `.rodata`
+    strings: Vec<String>
 }
 
 impl Elf {
-    pub fn new(res_file: impl Into<String>, ident_count: u16, program: Vec<u8>, asm_idents: Vec<(u64, u64, bool)>) -> Self {
+    /// За ячейками переменных в `.data` следуют строки сообщений о выходе
+    /// за границы и о делении на ноль, массивы размещаются в `.bss` и места
+    /// в файле не занимают. Константам ячейки не нужны: их значения подставлены в код
+    pub fn new(ident_count: u16, program: Vec<u8>, asm_idents: Vec<(u64, u64, bool)>, arrays: Vec<(u64, u64)>, constants: &HashSet<u64>, messages: (Option<String>, Option<String>), strings: Vec<String>) -> Self {
+        let mut offset = 0x12;
+        let slots = (0..=ident_count as u64).filter(|id| !constants.contains(id)).map(|id| {
+            offset += 8;
+            (id, offset - 8)
+        }).collect();
+        let mut storage = 0;
+        let arrays = arrays.into_iter().map(|(id, len)| {
+            storage += 8 * len;
+            (id, (storage - 8 * len, len))
+        }).collect();
+        let (message, division) = messages;
+        let division_offset = offset + message.as_ref().map_or(0, |v| v.len() as u64 + 1);
         Self {
-            res_file: res_file.into(),
             position: 0,
-            ident_count: ident_count.into(),
+            ident_count,
             ident_name_offset: Vec::new(),
             program,
-            asm_idents
+            asm_idents,
+            slots,
+            arrays,
+            message,
+            message_offset: offset,
+            division,
+            division_offset,
+            dprintf_name_offset: 0,
+            pow_name_offset: 0,
+            strings
         }
     }
 
-    pub fn process(&mut self) -> Result<(), Error> {
-        if Path::new(&self.res_file).exists() {
-            fs::remove_file(self.res_file.clone())?;
-        }
-        let mut file = File::create(self.res_file.clone())?;
+    pub fn process(&mut self) -> Result<Vec<u8>, Error> {
+        let mut file = Cursor::new(Vec::new());
         let header = self.get_header();
         let header = header.as_bytes();
-        file.write(header)?;
+        file.write_all(header)?;
         // смещение на позицию после заголовков секций
-        self.position = 0x80 + 0x40 * 6;
+        self.position = 0x80 + 0x40 * 8;
         file.seek(SeekFrom::Start(self.position))?;
 
         let (size, data) = self.get_data();
         let data = data.as_slice();
-        file.write(data)?;
+        file.write_all(data)?;
         self.position = file.stream_position()?;
         
         file.seek(SeekFrom::Start(0x80))?;
-        file.write(&Section::data(size).to_vec())?;
+        file.write_all(&Section::data(size).to_vec())?;
+
+        file.seek(SeekFrom::Start(0x40 + 0x40 * 8))?;
+        let storage = self.arrays.values().map(|(_, len)| 8 * len).sum();
+        file.write_all(&Section::bss(self.position, storage).to_vec())?;
 
         file.seek(SeekFrom::Start(0x40 + 0x40 * 2))?;
-        file.write(&Section::text(self.position, self.program.len() as u64).to_vec())?;
+        file.write_all(&Section::text(self.position, self.program.len() as u64).to_vec())?;
 
         file.seek(SeekFrom::Start(self.position))?;
-        file.write(self.program.as_slice())?;
+        file.write_all(self.program.as_slice())?;
         self.position = file.stream_position()?;
 
         for _ in 0..(16 - self.position % 16) {
-            file.write(&[0x00])?;
+            file.write_all(&[0x00])?;
+        }
+        self.position = file.stream_position()?;
+
+        let rodata = self.get_rodata();
+        file.seek(SeekFrom::Start(0x40 + 0x40 * 7))?;
+        file.write_all(&Section::rodata(self.position, rodata.len() as u64).to_vec())?;
+        file.seek(SeekFrom::Start(self.position))?;
+        file.write_all(rodata.as_slice())?;
+        self.position = file.stream_position()?;
+
+        for _ in 0..(16 - self.position % 16) {
+            file.write_all(&[0x00])?;
         }
         self.position = file.stream_position()?;
 
         file.seek(SeekFrom::Start(0x40 + 0x40 * 3))?;
-        file.write(&Section::shstrtab(self.position).to_vec())?;
+        file.write_all(&Section::shstrtab(self.position).to_vec())?;
         file.seek(SeekFrom::Start(self.position))?;
-        file.write(include_bytes!("./data.bin"))?;
+        file.write_all(include_bytes!("./data.bin"))?;
         self.position = file.stream_position()?;
 
         let strtab = self.get_strtab();
 
         file.seek(SeekFrom::Start(0x40 + 0x40 * 4))?;
         let (info, symtab) = self.get_symtab();
-        file.write(&Section::symtab(self.position, symtab.len() as u64, 5, info).to_vec())?;
+        file.write_all(&Section::symtab(self.position, symtab.len() as u64, 5, info).to_vec())?;
         
         file.seek(SeekFrom::Start(self.position))?;
-        file.write(symtab.as_slice())?;
+        file.write_all(symtab.as_slice())?;
         self.position = file.stream_position()?;
 
         for _ in 0..(16 - self.position % 16) {
-            file.write(&[0x00])?;
+            file.write_all(&[0x00])?;
         }
         self.position = file.stream_position()?;
 
         file.seek(SeekFrom::Start(0x40 + 0x40 * 5))?;
-        file.write(&Section::strtab(self.position, strtab.len() as u64).to_vec())?;
+        file.write_all(&Section::strtab(self.position, strtab.len() as u64).to_vec())?;
 
         file.seek(SeekFrom::Start(self.position))?;
-        file.write(strtab.as_slice())?;
+        file.write_all(strtab.as_slice())?;
         self.position = file.stream_position()?;
 
         for _ in 0..(16 - self.position % 16) {
-            file.write(&[0x00])?;
+            file.write_all(&[0x00])?;
         }
         self.position = file.stream_position()?;
 
         let rela_text = self.get_rela_text();
 
         file.seek(SeekFrom::Start(0x40 + 0x40 * 6))?;
-        file.write(&Section::rela_text(self.position, rela_text.len() as u64, 4, 2).to_vec())?;
+        file.write_all(&Section::rela_text(self.position, rela_text.len() as u64, 4, 2).to_vec())?;
 
         file.seek(SeekFrom::Start(self.position))?;
-        file.write(rela_text.as_slice())?;
+        file.write_all(rela_text.as_slice())?;
         self.position = file.stream_position()?;
 
         for _ in 0..(16 - self.position % 16) {
-            file.write(&[0x00])?;
+            file.write_all(&[0x00])?;
         }
         
-        Ok(())
+        Ok(file.into_inner())
     }
 
     fn get_header(&self) -> String {
@@ -115,7 +164,7 @@ impl Elf {
         header += "\x7fELF\x02\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00";
         header += "\x01\x00\x3e\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00";
         header += "\x00\x00\x00\x00\x00\x00\x00\x00\x40\x00\x00\x00\x00\x00\x00\x00";
-        header += "\x00\x00\x00\x00\x40\x00\x00\x00\x00\x00\x40\x00\x07\x00\x03\x00";
+        header += "\x00\x00\x00\x00\x40\x00\x00\x00\x00\x00\x40\x00\x09\x00\x03\x00";
 
         header
     }
@@ -123,8 +172,10 @@ impl Elf {
     fn get_data(&self) -> (u64, Vec<u8>) {
         let mut data = Vec::new();
         data.append(&mut b"\x25\x6c\x64\x00\x25\x6c\x64\x0a\x00\x25\x6c\x66\x00\x25\x6c\x66\x0a\x00".to_vec());
-        for _ in 0..self.ident_count+1 {
-            data.append(&mut b"\x00\x00\x00\x00\x00\x00\x00\x00".to_vec());
+        data.resize(data.len() + 8 * self.slots.len(), 0);
+        for message in self.message.iter().chain(&self.division) {
+            data.append(&mut message.as_bytes().to_vec());
+            data.push(0);
         }
 
         let size = data.len();
@@ -136,38 +187,87 @@ impl Elf {
         (size as u64, data)
     }
 
+    /// Формат `%s` и строковые литералы, каждый с завершающим нулём
+    fn get_rodata(&self) -> Vec<u8> {
+        let mut data = b"%s\0".to_vec();
+        for string in &self.strings {
+            data.append(&mut string.as_bytes().to_vec());
+            data.push(0);
+        }
+        data
+    }
+
+    fn string_offset(&self, index: usize) -> i64 {
+        3 + self.strings[..index].iter().map(|s| s.len() as i64 + 1).sum::<i64>()
+    }
+
+    /// Сообщения об ошибках выполнения печатаются через `dprintf`
+    fn uses_dprintf(&self) -> bool {
+        self.message.is_some() || self.division.is_some()
+    }
+
+    /// Вещественное возведение в степень вызывает `pow` из libm
+    fn uses_pow(&self) -> bool {
+        let id = self.ident_count as u64 + 11;
+        self.asm_idents.iter().any(|(i, _, _)| *i == id)
+    }
+
     fn get_symtab(&mut self) -> (u32, Vec<u8>) {
         let mut data = Vec::new();
 
         data.append(&mut Symtab::null().to_vec());
         data.append(&mut Symtab::data().to_vec());
         data.append(&mut Symtab::text().to_vec());
-        
+        data.append(&mut Symtab::rodata().to_vec());
+
         data.append(&mut Symtab::ident(1, 0).to_vec());
         data.append(&mut Symtab::ident(4, 4).to_vec());
         data.append(&mut Symtab::ident(7, 9).to_vec());
         data.append(&mut Symtab::ident(11, 13).to_vec());
 
         for i in 0..self.ident_count+1 {
-            data.append(&mut Symtab::ident(self.ident_name_offset[i as usize], 0x12 + i as u64 * 8).to_vec());
+            let name = self.ident_name_offset[i as usize];
+            let symbol = match self.arrays.get(&(i as u64)) {
+                Some((offset, len)) => Symtab::array(name, *offset, 8 * len),
+                None => match self.slots.get(&(i as u64)) {
+                    Some(offset) => Symtab::variable(name, *offset, 8),
+                    None => Symtab::variable(name, 0, 0)
+                }
+            };
+            data.append(&mut symbol.to_vec());
         }
 
         data.append(&mut Symtab::scanf().to_vec());
         data.append(&mut Symtab::printf().to_vec());
 
         data.append(&mut Symtab::main().to_vec());
+        data.append(&mut Symtab::exit().to_vec());
+        if self.uses_dprintf() {
+            data.append(&mut Symtab::external(self.dprintf_name_offset).to_vec());
+        }
+        if self.uses_pow() {
+            data.append(&mut Symtab::external(self.pow_name_offset).to_vec());
+        }
 
-        (8+self.ident_count as u32, data)
+        (9+self.ident_count as u32, data)
     }
 
     fn get_strtab(&mut self) -> Vec<u8> {
         let mut data = Vec::new();
-        data.append(&mut b"\0if\0of\0iff\0off\0printf\0main\0scanf\0".to_vec());
+        data.append(&mut b"\0if\0of\0iff\0off\0printf\0main\0scanf\0exit\0".to_vec());
 
         for i in 0..self.ident_count+1 {
             self.ident_name_offset.push(data.len() as u32);
             data.append(&mut format!("i{}\0", i).as_bytes().to_vec());
         }
+        if self.uses_dprintf() {
+            self.dprintf_name_offset = data.len() as u32;
+            data.append(&mut b"dprintf\0".to_vec());
+        }
+        if self.uses_pow() {
+            self.pow_name_offset = data.len() as u32;
+            data.append(&mut b"pow\0".to_vec());
+        }
 
         data
     }
@@ -187,9 +287,27 @@ impl Elf {
                 id if id == max+4 =>
                     data.append(&mut Rela::format(pos, 13, big).to_vec()),
                 id if id == max+5 =>
-                    data.append(&mut Rela::scanf(pos, 8 + self.ident_count as u64).to_vec()),
+                    data.append(&mut Rela::scanf(pos, 9 + self.ident_count as u64).to_vec()),
                 id if id == max+6 =>
-                    data.append(&mut Rela::printf(pos, 9 + self.ident_count as u64).to_vec()),
+                    data.append(&mut Rela::printf(pos, 10 + self.ident_count as u64).to_vec()),
+                id if id == max+7 =>
+                    data.append(&mut Rela::exit(pos, 12 + self.ident_count as u64).to_vec()),
+                id if id == max+8 =>
+                    data.append(&mut Rela::format(pos, self.message_offset as i64, big).to_vec()),
+                id if id == max+9 =>
+                    data.append(&mut Rela::call(pos, 13 + self.ident_count as u64).to_vec()),
+                id if id == max+11 => {
+                    let index = 13 + self.ident_count as u64 + self.uses_dprintf() as u64;
+                    data.append(&mut Rela::call(pos, index).to_vec())
+                },
+                id if id == max+10 =>
+                    data.append(&mut Rela::rodata(pos, 0).to_vec()),
+                id if id == max+12 =>
+                    data.append(&mut Rela::format(pos, self.division_offset as i64, big).to_vec()),
+                id if id > max+12 =>
+                    data.append(&mut Rela::rodata(pos, self.string_offset((id - max - 13) as usize)).to_vec()),
+                id if self.arrays.contains_key(&id) =>
+                    data.append(&mut Rela::array(pos, 8 + id, big).to_vec()),
                 id if big => data.append(&mut Rela::x8(pos, id).to_vec()),
                 id => data.append(&mut Rela::x4(pos, id).to_vec())
             }
@@ -37,6 +37,41 @@ impl Rela {
         }
     }
 
+    pub fn exit(offset: u64, index: u64) -> Self {
+        Self {
+            addr: offset,
+            info: 2 + (index << 32),
+            addend: -4
+        }
+    }
+
+    /// Вызов внешней функции, подключаемой только при необходимости
+    pub fn call(offset: u64, index: u64) -> Self {
+        Self {
+            addr: offset,
+            info: 2 + (index << 32),
+            addend: -4
+        }
+    }
+
+    /// Абсолютный адрес в `.rodata` через символ секции
+    pub fn rodata(offset: u64, pos: i64) -> Self {
+        Self {
+            addr: offset,
+            info: 0x300000001,
+            addend: pos
+        }
+    }
+
+    /// Адрес глобального массива через его собственный символ
+    pub fn array(offset: u64, index: u64, big: bool) -> Self {
+        Self {
+            addr: offset,
+            info: (index << 32) + if big {1} else {0xb},
+            addend: 0
+        }
+    }
+
     pub fn format(offset: u64, pos: i64, big: bool) -> Self {
         Self {
             addr: offset,
@@ -19,7 +19,7 @@ impl Section {
             s_type: 1,
             flags: 3,
             addr: 0,
-            offset: 0x80 + 0x40 * 6,
+            offset: 0x80 + 0x40 * 8,
             size,
             link: 0,
             info: 0,
@@ -43,6 +43,38 @@ impl Section {
         }
     }
 
+    pub fn rodata(offset: u64, size: u64) -> Self {
+        Self {
+            name: 0x32,
+            s_type: 1,
+            flags: 2,
+            addr: 0,
+            offset,
+            size,
+            link: 0,
+            info: 0,
+            addr_align: 1,
+            ent_size: 0
+        }
+    }
+
+    /// Глобальные массивы: место без содержимого в файле, которое
+    /// обнуляется при загрузке
+    pub fn bss(offset: u64, size: u64) -> Self {
+        Self {
+            name: 0x3a,
+            s_type: 8,
+            flags: 3,
+            addr: 0,
+            offset,
+            size,
+            link: 0,
+            info: 0,
+            addr_align: 8,
+            ent_size: 0
+        }
+    }
+
     pub fn shstrtab(offset: u64) -> Self {
         Self {
             name: 0x0d,
@@ -50,7 +82,7 @@ impl Section {
             flags: 0,
             addr: 0,
             offset,
-            size: 0x32,
+            size: 0x3f,
             link: 0,
             info: 0,
             addr_align: 1,
@@ -105,7 +137,7 @@ impl Section {
 }
 
 impl Section {
-    pub fn to_vec(self) -> Vec<u8> {
+    pub fn to_vec(&self) -> Vec<u8> {
         let mut section = Vec::new();
 
         section.append(&mut self.name.to_le_bytes().to_vec());
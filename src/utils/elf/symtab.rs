@@ -41,6 +41,17 @@ impl Symtab {
         }
     }
 
+    pub fn rodata() -> Self {
+        Self  {
+            name: 0,
+            info: 3,
+            other: 0,
+            shndx: 7,
+            value: 0,
+            size: 0,
+        }
+    }
+
     pub fn main() -> Self {
         Self {
             name: 0x16,
@@ -74,6 +85,53 @@ impl Symtab {
         }
     }
 
+    pub fn exit() -> Self {
+        Self {
+            name: 0x21,
+            info: 0x10,
+            other: 0,
+            shndx: 0,
+            value: 0,
+            size: 0,
+        }
+    }
+
+    /// Внешняя функция с именем по смещению в `.strtab`
+    pub fn external(name: u32) -> Self {
+        Self {
+            name,
+            info: 0x10,
+            other: 0,
+            shndx: 0,
+            value: 0,
+            size: 0,
+        }
+    }
+
+    /// Переменная в `.data` с размером в байтах
+    pub fn variable(name: u32, value: u64, size: u64) -> Self {
+        Self {
+            name,
+            info: 0,
+            other: 0,
+            shndx: 1,
+            value,
+            size,
+        }
+    }
+
+    /// Глобальный массив в `.bss` с размером в байтах
+    pub fn array(name: u32, value: u64, size: u64) -> Self {
+        Self {
+            name,
+            info: 0,
+            other: 0,
+            shndx: 8,
+            value,
+            size,
+        }
+    }
+
     pub fn ident(name: u32, value: u64) -> Self {
         Self {
             name,
@@ -0,0 +1,21 @@
+use crate::utils::structs::span::Span;
+
+pub type RuntimeResult<T> = Result<T, RuntimeError>;
+
+#[derive(Debug, Clone)]
+pub enum RuntimeError {
+    /// Целочисленное деление на ноль или переполнение при делении,
+    /// машинный код проверяет делитель перед `idiv`
+    DivisionError(Span),
+    Io(String),
+    /// Индекс вне границ массива и длина массива
+    IndexOutOfBounds(Span, i64, u64),
+    /// Не хватило памяти под массив с этой длиной
+    ArrayAllocation(Span, u64)
+}
+
+impl From<std::io::Error> for RuntimeError {
+    fn from(value: std::io::Error) -> Self {
+        RuntimeError::Io(value.to_string())
+    }
+}
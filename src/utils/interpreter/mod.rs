@@ -0,0 +1,702 @@
+use std::{collections::HashMap, fmt::Display, io::{BufRead, Write}, rc::Rc};
+
+use super::structs::{span::Span, program::{Expression, Function, Ident, MainOperation, Multiplier, Operand, Operator, OutputItem, Place, Term}, types::{AdditionOperations, LexerDigitalData, MultiplicationOperations, ProgramTypes, RelationOperations}};
+
+pub mod error;
+
+use error::{RuntimeError, RuntimeResult};
+
+/// Значение переменной или выражения во время выполнения
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool)
+}
+
+impl Value {
+    fn default_for(t: &ProgramTypes) -> Self {
+        match t {
+            ProgramTypes::Integer(_) => Value::Integer(0),
+            ProgramTypes::Float(_) => Value::Float(0.),
+            ProgramTypes::Boolean(_) => Value::Boolean(false),
+            ProgramTypes::Array(t, _) => Value::default_for(t)
+        }
+    }
+
+    /// Целое значение как вещественное, остальные без изменений
+    fn to_float(self) -> Self {
+        match self {
+            Value::Integer(v) => Value::Float(v as f64),
+            v => v
+        }
+    }
+
+    /// Отбрасывание дробной части как у `cvttsd2si`: при переполнении
+    /// и для NaN получается наименьшее целое
+    pub(crate) fn to_integer(self) -> Self {
+        match self {
+            Value::Float(v) if !(i64::MIN as f64..-(i64::MIN as f64)).contains(&v) => Value::Integer(i64::MIN),
+            Value::Float(v) => Value::Integer(v as i64),
+            v => v
+        }
+    }
+
+    fn as_bool(&self) -> bool {
+        match self {
+            Value::Boolean(v) => *v,
+            Value::Integer(v) => *v != 0,
+            Value::Float(v) => *v != 0.
+        }
+    }
+}
+
+/// Вывод в том же виде, что и `printf("%ld")`/`printf("%lf")` в машинном коде
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Integer(v) => write!(f, "{}", v),
+            Value::Boolean(v) => write!(f, "{}", *v as u8),
+            Value::Float(v) if v.is_nan() => write!(f, "{}nan", if v.is_sign_negative() { "-" } else { "" }),
+            Value::Float(v) if v.is_infinite() => write!(f, "{}inf", if v.is_sign_negative() { "-" } else { "" }),
+            Value::Float(v) => write!(f, "{:.6}", v)
+        }
+    }
+}
+
+/// Чем закончилось выполнение оператора
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return
+}
+
+/// Параметры и локальные переменные вызванной процедуры или функции,
+/// а вне функций - переменные вложенных блоков основной программы
+#[derive(Default)]
+struct Frame {
+    types: HashMap<u64, ProgramTypes>,
+    values: HashMap<u64, Value>,
+    arrays: HashMap<u64, Vec<Value>>
+}
+
+/// Интерпретатор дерева программы. Служит эталонной семантикой
+/// для машинного кода, который порождает `Semantic`, поэтому
+/// программа должна быть заранее проверена семантическим анализатором
+pub struct Interpreter<R: BufRead, W: Write> {
+    program: Vec<MainOperation>,
+    vars: HashMap<u64, LexerDigitalData>,
+    strings: Vec<String>,
+    types: HashMap<u64, ProgramTypes>,
+    values: HashMap<u64, Value>,
+    arrays: HashMap<u64, Vec<Value>>,
+    functions: HashMap<u64, Rc<Function>>,
+    frames: Vec<Frame>,
+    /// Значение последнего выполненного `return`
+    result: Option<Value>,
+    input: R,
+    output: W
+}
+
+impl<R: BufRead, W: Write> Interpreter<R, W> {
+    pub fn new(program: Vec<MainOperation>, vars: HashMap<u64, LexerDigitalData>, strings: Vec<String>, input: R, output: W) -> Self {
+        Self {
+            program,
+            vars,
+            strings,
+            types: HashMap::new(),
+            values: HashMap::new(),
+            arrays: HashMap::new(),
+            functions: HashMap::new(),
+            frames: Vec::new(),
+            result: None,
+            input,
+            output
+        }
+    }
+
+    pub fn run_process(&mut self) -> RuntimeResult<()> {
+        let program = std::mem::take(&mut self.program);
+        for main_operation in &program {
+            if let MainOperation::Function(function) = main_operation {
+                self.functions.insert(function.ident.id, Rc::new(function.clone()));
+            }
+        }
+        let res = self.run_block(&program);
+        self.program = program;
+        res?;
+        self.output.flush()?;
+        Ok(())
+    }
+
+    fn run_block(&mut self, block: &[MainOperation]) -> RuntimeResult<Flow> {
+        for main_operation in block {
+            match main_operation {
+                MainOperation::CreateVariable(ident_vec) =>
+                    for (identifiers, identifiers_type) in ident_vec {
+                        for ident in identifiers {
+                            self.declare(ident, identifiers_type)?;
+                        }
+                    },
+                MainOperation::CreateConstant(ident, expression) => self.define(ident, expression)?,
+                MainOperation::Operator(operator) =>
+                    if self.run_operator(operator)? == Flow::Return {
+                        return Ok(Flow::Return);
+                    },
+                MainOperation::Function(_) => ()
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    /// Блок составного оператора. Объявленные в нём переменные перекрывают
+    /// одноимённые до конца блока, затем прежние значения восстанавливаются
+    fn run_scope(&mut self, block: &[MainOperation]) -> RuntimeResult<Flow> {
+        let mut shadowed = Vec::new();
+        let mut flow = Ok(Flow::Next);
+        for main_operation in block {
+            match main_operation {
+                MainOperation::CreateVariable(ident_vec) =>
+                    for (identifiers, identifiers_type) in ident_vec {
+                        for ident in identifiers {
+                            shadowed.push(self.unbind(ident.id));
+                            self.declare(ident, identifiers_type)?;
+                        }
+                    },
+                MainOperation::CreateConstant(ident, expression) => {
+                    shadowed.push(self.unbind(ident.id));
+                    self.define(ident, expression)?;
+                },
+                MainOperation::Operator(operator) => match self.run_operator(operator) {
+                    Ok(Flow::Next) => (),
+                    res => {
+                        flow = res;
+                        break;
+                    }
+                },
+                MainOperation::Function(_) => ()
+            }
+        }
+
+        let frame = self.frames.last_mut().unwrap();
+        for (id, t, value, array) in shadowed.into_iter().rev() {
+            restore(&mut frame.types, id, t);
+            restore(&mut frame.values, id, value);
+            restore(&mut frame.arrays, id, array);
+        }
+        flow
+    }
+
+    /// Забирает из кадра привязку имени, которое перекрывается в блоке
+    fn unbind(&mut self, id: u64) -> (u64, Option<ProgramTypes>, Option<Value>, Option<Vec<Value>>) {
+        let frame = self.frames.last_mut().unwrap();
+        (id, frame.types.remove(&id), frame.values.remove(&id), frame.arrays.remove(&id))
+    }
+
+    /// Константа хранится как переменная с типом своего значения
+    fn define(&mut self, ident: &Ident, expression: &Expression) -> RuntimeResult<()> {
+        let value = self.eval_expression(expression)?;
+        let t = match value {
+            Value::Integer(_) => ProgramTypes::Integer(None),
+            Value::Float(_) => ProgramTypes::Float(None),
+            Value::Boolean(_) => ProgramTypes::Boolean(None)
+        };
+        self.declare(ident, &t)?;
+        self.store(ident.id, value);
+        Ok(())
+    }
+
+    /// Переменная текущей функции или блока, а вне них глобальная.
+    /// Нехватка памяти под массив завершает программу с ошибкой
+    fn declare(&mut self, ident: &Ident, t: &ProgramTypes) -> RuntimeResult<()> {
+        let (types, values, arrays) = match self.frames.last_mut() {
+            Some(frame) => (&mut frame.types, &mut frame.values, &mut frame.arrays),
+            None => (&mut self.types, &mut self.values, &mut self.arrays)
+        };
+        if let ProgramTypes::Array(_, len) = t {
+            let mut array = Vec::new();
+            if array.try_reserve_exact(*len as usize).is_err() {
+                return Err(RuntimeError::ArrayAllocation(ident.span, *len));
+            }
+            array.resize(*len as usize, Value::default_for(t));
+            arrays.insert(ident.id, array);
+        } else {
+            values.insert(ident.id, Value::default_for(t));
+        }
+        types.insert(ident.id, t.clone());
+        Ok(())
+    }
+
+    fn type_of(&self, id: u64) -> Option<&ProgramTypes> {
+        match self.frames.last().and_then(|frame| frame.types.get(&id)) {
+            Some(t) => Some(t),
+            None => self.types.get(&id)
+        }
+    }
+
+    fn load(&self, id: u64) -> Value {
+        match self.frames.last().and_then(|frame| frame.values.get(&id)) {
+            Some(v) => *v,
+            None => self.values.get(&id).copied().unwrap_or(Value::Integer(0))
+        }
+    }
+
+    fn store(&mut self, id: u64, mut value: Value) {
+        if let Some(ProgramTypes::Float(_)) = self.type_of(id) {
+            value = value.to_float();
+        }
+        match self.frames.last_mut() {
+            Some(frame) if frame.values.contains_key(&id) => frame.values.insert(id, value),
+            _ => self.values.insert(id, value)
+        };
+    }
+
+    /// Номер элемента массива с проверкой границ, как в машинном коде
+    fn index(&mut self, ident: &Ident, index: &Expression, span: Span) -> RuntimeResult<usize> {
+        let len = match self.type_of(ident.id) {
+            Some(ProgramTypes::Array(_, len)) => *len,
+            _ => 0
+        };
+        match self.eval_expression(index)? {
+            Value::Integer(i) if (i as u64) < len => Ok(i as usize),
+            Value::Integer(i) => Err(RuntimeError::IndexOutOfBounds(span, i, len)),
+            _ => Err(RuntimeError::IndexOutOfBounds(span, 0, len))
+        }
+    }
+
+    fn array(&mut self, id: u64) -> &mut Vec<Value> {
+        match self.frames.last_mut() {
+            Some(frame) if frame.arrays.contains_key(&id) => frame.arrays.get_mut(&id).unwrap(),
+            _ => self.arrays.entry(id).or_default()
+        }
+    }
+
+    fn store_element(&mut self, id: u64, index: usize, mut value: Value) {
+        if let Some(ProgramTypes::Array(t, _)) = self.type_of(id) {
+            if let ProgramTypes::Float(_) = **t {
+                value = value.to_float();
+            }
+        }
+        self.array(id)[index] = value;
+    }
+
+    fn call(&mut self, ident: &Ident, arguments: &[Expression]) -> RuntimeResult<Value> {
+        let function = self.functions[&ident.id].clone();
+        let mut frame = Frame::default();
+        for (argument, (param, param_type)) in arguments.iter().zip(&function.params) {
+            let mut value = self.eval_expression(argument)?;
+            if let ProgramTypes::Float(_) = param_type {
+                value = value.to_float();
+            }
+            frame.types.insert(param.id, param_type.clone());
+            frame.values.insert(param.id, value);
+        }
+
+        self.frames.push(frame);
+        let res = self.run_block(&function.body);
+        self.frames.pop();
+        res?;
+
+        // без return результат нулевой, как в машинном коде
+        let value = match (self.result.take(), &function.result) {
+            (Some(value), Some(ProgramTypes::Float(_))) => value.to_float(),
+            (Some(value), _) => value,
+            (None, Some(t)) => Value::default_for(t),
+            (None, None) => Value::Integer(0)
+        };
+        Ok(value)
+    }
+
+    /// Значения переменных после выполнения
+    pub fn values(&self) -> &HashMap<u64, Value> {
+        &self.values
+    }
+
+    fn run_operator(&mut self, operator: &Operator) -> RuntimeResult<Flow> {
+        match operator {
+            Operator::Assignment(Place::Variable(ident), expression) => {
+                let value = self.eval_expression(expression)?;
+                self.store(ident.id, value);
+            },
+            // индекс вычисляется и проверяется раньше значения, как в машинном коде
+            Operator::Assignment(Place::Element(ident, index, span), expression) => {
+                let index = self.index(ident, index, *span)?;
+                let value = self.eval_expression(expression)?;
+                self.store_element(ident.id, index, value);
+            },
+            Operator::Composite(block) => {
+                // переменные блоков основной программы не должны
+                // перекрывать глобальные для вызываемых функций
+                let outermost = self.frames.is_empty();
+                if outermost {
+                    self.frames.push(Frame::default());
+                }
+                let res = self.run_scope(block);
+                if outermost {
+                    self.frames.pop();
+                }
+                return res;
+            },
+            Operator::Output(items) =>
+                for item in items {
+                    match item {
+                        OutputItem::Expression(expression) => {
+                            let value = self.eval_expression(expression)?;
+                            writeln!(self.output, "{}", value)?;
+                        },
+                        OutputItem::Text(id, _) => write!(self.output, "{}", self.strings[*id as usize])?
+                    }
+                },
+            Operator::Input(ids) => {
+                self.output.flush()?;
+                for place in ids {
+                    self.input(place)?;
+                }
+            },
+            Operator::If(expression, operator1, operator2) => {
+                if self.eval_expression(expression)?.as_bool() {
+                    return self.run_operator(operator1);
+                } else if let Some(operator2) = operator2 {
+                    return self.run_operator(operator2);
+                }
+            },
+            Operator::For(init, condition, step, operator) => {
+                if let Some(init) = init {
+                    self.run_operator(init)?;
+                }
+                loop {
+                    if let Some(condition) = condition {
+                        if !self.eval_expression(condition)?.as_bool() {
+                            break;
+                        }
+                    }
+                    match self.run_operator(operator)? {
+                        Flow::Break => break,
+                        Flow::Return => return Ok(Flow::Return),
+                        _ => ()
+                    }
+                    if let Some(step) = step {
+                        self.run_operator(step)?;
+                    }
+                }
+            },
+            Operator::ForConditions(expressions, operator) => {
+                // все условия заголовка проверяются вместе, как в машинном коде
+                while self.eval_conditions(expressions)? {
+                    match self.run_operator(operator)? {
+                        Flow::Break => break,
+                        Flow::Return => return Ok(Flow::Return),
+                        _ => ()
+                    }
+                }
+            },
+            Operator::While(expression, operator) =>
+                while self.eval_expression(expression)?.as_bool() {
+                    match self.run_operator(operator)? {
+                        Flow::Break => break,
+                        Flow::Return => return Ok(Flow::Return),
+                        _ => ()
+                    }
+                },
+            Operator::Case(selector, arms, default) => {
+                let value = self.eval_expression(selector)?;
+                for (labels, operator) in arms {
+                    for label in labels {
+                        if self.eval_expression(label)? == value {
+                            return self.run_operator(operator);
+                        }
+                    }
+                }
+                if let Some(default) = default {
+                    return self.run_operator(default);
+                }
+            },
+            Operator::Break(_) => return Ok(Flow::Break),
+            Operator::Continue(_) => return Ok(Flow::Continue),
+            Operator::Call(ident, arguments, _) => {
+                self.call(ident, arguments)?;
+            },
+            Operator::Return(expression, _) => {
+                self.result = match expression {
+                    Some(expression) => Some(self.eval_expression(expression)?),
+                    None => None
+                };
+                return Ok(Flow::Return);
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn eval_conditions(&mut self, expressions: &[Expression]) -> RuntimeResult<bool> {
+        let mut res = true;
+        for expression in expressions {
+            res &= self.eval_expression(expression)?.as_bool();
+        }
+        Ok(res)
+    }
+
+    fn eval_expression(&mut self, expression: &Expression) -> RuntimeResult<Value> {
+        let mut operands = expression.operands.iter();
+        let mut left = self.eval_operand(operands.next().unwrap())?;
+
+        for (operand, operation) in operands.zip(expression.operations.iter()) {
+            let right = self.eval_operand(operand)?;
+            let res = match widen(left, right) {
+                (Value::Integer(a), Value::Integer(b)) => compare(a, b, operation),
+                (Value::Float(a), Value::Float(b)) => compare(a, b, operation),
+                (a, b) => compare(a.as_bool(), b.as_bool(), operation)
+            };
+            left = Value::Boolean(res);
+        }
+
+        Ok(left)
+    }
+
+    fn eval_operand(&mut self, operand: &Operand) -> RuntimeResult<Value> {
+        let mut terms = operand.terms.iter();
+        let mut left = self.eval_term(terms.next().unwrap())?;
+
+        for (term, operation) in terms.zip(operand.operations.iter()) {
+            // правый операнд `||` не вычисляется, если результат уже известен
+            if let (AdditionOperations::Or, Value::Boolean(true)) = (operation, left) {
+                continue;
+            }
+            let right = self.eval_term(term)?;
+            let (a, b) = widen(left, right);
+            left = match (a, b, operation) {
+                (Value::Integer(a), Value::Integer(b), AdditionOperations::Addition) => Value::Integer(a.wrapping_add(b)),
+                (Value::Integer(a), Value::Integer(b), _) => Value::Integer(a.wrapping_sub(b)),
+                (Value::Float(a), Value::Float(b), AdditionOperations::Addition) => Value::Float(a + b),
+                (Value::Float(a), Value::Float(b), _) => Value::Float(a - b),
+                (a, b, _) => Value::Boolean(a.as_bool() || b.as_bool())
+            };
+        }
+
+        Ok(left)
+    }
+
+    fn eval_term(&mut self, term: &Term) -> RuntimeResult<Value> {
+        let mut multipliers = term.multipliers.iter();
+        let mut left = self.eval_multiplier(multipliers.next().unwrap())?;
+
+        for (multiplier, operation) in multipliers.zip(term.operations.iter()) {
+            if let (MultiplicationOperations::And, Value::Boolean(false)) = (operation, left) {
+                continue;
+            }
+            let right = self.eval_multiplier(multiplier)?;
+            let (a, b) = widen(left, right);
+            left = match (a, b, operation) {
+                (Value::Integer(a), Value::Integer(b), MultiplicationOperations::Multiplication) => Value::Integer(a.wrapping_mul(b)),
+                (Value::Integer(a), Value::Integer(b), MultiplicationOperations::Remainder) => match a.checked_rem(b) {
+                    Some(v) => Value::Integer(v),
+                    None => return Err(RuntimeError::DivisionError(multiplier.span()))
+                },
+                (Value::Integer(a), Value::Integer(b), _) => match a.checked_div(b) {
+                    Some(v) => Value::Integer(v),
+                    None => return Err(RuntimeError::DivisionError(multiplier.span()))
+                },
+                (Value::Float(a), Value::Float(b), MultiplicationOperations::Multiplication) => Value::Float(a * b),
+                (Value::Float(a), Value::Float(b), _) => Value::Float(a / b),
+                (a, b, _) => Value::Boolean(a.as_bool() && b.as_bool())
+            };
+        }
+
+        Ok(left)
+    }
+
+    fn eval_multiplier(&mut self, multiplier: &Multiplier) -> RuntimeResult<Value> {
+        Ok(match multiplier {
+            Multiplier::Identifier(ident) => self.load(ident.id),
+            Multiplier::Call(ident, arguments, _) => self.call(ident, arguments)?,
+            Multiplier::Index(ident, index, span) => {
+                let index = self.index(ident, index, *span)?;
+                self.array(ident.id)[index]
+            },
+            Multiplier::Variable(id, _) => match self.vars.get(id) {
+                Some(LexerDigitalData::Float(v)) => Value::Float(*v),
+                Some(LexerDigitalData::Integer(v)) => Value::Integer(*v),
+                None => Value::Integer(0)
+            },
+            Multiplier::Boolean(b, _) => Value::Boolean(*b),
+            Multiplier::Not(m, _) => Value::Boolean(!self.eval_multiplier(m)?.as_bool()),
+            Multiplier::Convert(ProgramTypes::Float(_), e, _) => self.eval_expression(e)?.to_float(),
+            Multiplier::Convert(_, e, _) => self.eval_expression(e)?.to_integer(),
+            Multiplier::Neg(m, _) => match self.eval_multiplier(m)? {
+                Value::Integer(v) => Value::Integer(v.wrapping_neg()),
+                Value::Float(v) => Value::Float(-v),
+                v => v
+            },
+            Multiplier::Power(base, exponent, span) => {
+                let base = self.eval_multiplier(base)?;
+                let exponent = self.eval_multiplier(exponent)?;
+                match widen(base, exponent) {
+                    (Value::Float(a), Value::Float(b)) => Value::Float(a.powf(b)),
+                    (Value::Integer(a), Value::Integer(b)) => match power(a, b) {
+                        Some(v) => Value::Integer(v),
+                        None => return Err(RuntimeError::DivisionError(*span))
+                    },
+                    (a, _) => a
+                }
+            },
+            Multiplier::Expression(e) => self.eval_expression(e)?,
+            Multiplier::Constant(value, _) => match value {
+                ProgramTypes::Integer(Some(v)) => Value::Integer(*v),
+                ProgramTypes::Float(Some(v)) => Value::Float(*v),
+                ProgramTypes::Boolean(Some(v)) => Value::Boolean(*v),
+                t => Value::default_for(t)
+            }
+        })
+    }
+
+    /// Читает значение так же, как `scanf`: при ошибке ввода
+    /// переменная сохраняет прежнее значение
+    fn input(&mut self, place: &Place) -> RuntimeResult<()> {
+        let ident = place.ident();
+        let index = match place {
+            Place::Element(_, index, span) => Some(self.index(&ident, index, *span)?),
+            Place::Variable(_) => None
+        };
+        let t = match self.type_of(ident.id) {
+            Some(ProgramTypes::Array(t, _)) => *t.clone(),
+            Some(t) => t.clone(),
+            None => ProgramTypes::Integer(None)
+        };
+        let float = matches!(t, ProgramTypes::Float(_));
+        self.skip_whitespace()?;
+        let word = if float { self.read_float()? } else { self.read_integer()? };
+
+        let value = match t {
+            ProgramTypes::Float(_) => word.parse().ok().map(Value::Float),
+            ProgramTypes::Boolean(_) => parse_integer(&word).map(|v| Value::Boolean(v != 0)),
+            _ => parse_integer(&word).map(Value::Integer)
+        };
+        match (value, index) {
+            (Some(value), Some(index)) => self.store_element(ident.id, index, value),
+            (Some(value), None) => self.store(ident.id, value),
+            (None, _) => ()
+        }
+        Ok(())
+    }
+
+    fn peek(&mut self) -> RuntimeResult<Option<u8>> {
+        Ok(self.input.fill_buf()?.first().copied())
+    }
+
+    fn skip_whitespace(&mut self) -> RuntimeResult<()> {
+        while let Some(c) = self.peek()? {
+            if !c.is_ascii_whitespace() {
+                break;
+            }
+            self.input.consume(1);
+        }
+        Ok(())
+    }
+
+    /// Забирает из ввода символы, пока `accept` их принимает
+    fn take_while(&mut self, word: &mut String, accept: impl Fn(u8) -> bool) -> RuntimeResult<()> {
+        while let Some(c) = self.peek()? {
+            if !accept(c) {
+                break;
+            }
+            word.push(c as char);
+            self.input.consume(1);
+        }
+        Ok(())
+    }
+
+    fn read_sign(&mut self, word: &mut String) -> RuntimeResult<()> {
+        if let Some(c @ (b'+' | b'-')) = self.peek()? {
+            word.push(c as char);
+            self.input.consume(1);
+        }
+        Ok(())
+    }
+
+    fn read_integer(&mut self) -> RuntimeResult<String> {
+        let mut word = String::new();
+        self.read_sign(&mut word)?;
+        self.take_while(&mut word, |c| c.is_ascii_digit())?;
+        Ok(word)
+    }
+
+    fn read_float(&mut self) -> RuntimeResult<String> {
+        let mut word = String::new();
+        self.read_sign(&mut word)?;
+        if let Some(b'i' | b'I' | b'n' | b'N') = self.peek()? {
+            self.take_while(&mut word, |c| c.is_ascii_alphabetic())?;
+            return Ok(word);
+        }
+        self.take_while(&mut word, |c| c.is_ascii_digit())?;
+        if self.peek()? == Some(b'.') {
+            word.push('.');
+            self.input.consume(1);
+            self.take_while(&mut word, |c| c.is_ascii_digit())?;
+        }
+        if let Some(b'e' | b'E') = self.peek()? {
+            word.push('e');
+            self.input.consume(1);
+            self.read_sign(&mut word)?;
+            self.take_while(&mut word, |c| c.is_ascii_digit())?;
+        }
+        Ok(word)
+    }
+}
+
+/// Разбор целого как у `strtol`: при переполнении значение насыщается
+fn parse_integer(word: &str) -> Option<i64> {
+    if !word.bytes().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    match word.parse::<i64>() {
+        Ok(v) => Some(v),
+        Err(_) if word.starts_with('-') => Some(i64::MIN),
+        Err(_) => Some(i64::MAX)
+    }
+}
+
+/// Целая степень как в машинном коде: при отрицательном показателе
+/// основание заменяется на 1 / a, переполнение отбрасывается
+pub(crate) fn power(mut a: i64, b: i64) -> Option<i64> {
+    if b < 0 {
+        a = 1i64.checked_div(a)?;
+    }
+    let (mut res, mut b) = (1i64, b.unsigned_abs());
+    while b != 0 {
+        if b & 1 == 1 {
+            res = res.wrapping_mul(a);
+        }
+        a = a.wrapping_mul(a);
+        b >>= 1;
+    }
+    Some(res)
+}
+
+/// Целый операнд в паре с вещественным расширяется до вещественного
+fn widen(a: Value, b: Value) -> (Value, Value) {
+    match (a, b) {
+        (Value::Integer(_), Value::Float(_)) => (a.to_float(), b),
+        (Value::Float(_), Value::Integer(_)) => (a, b.to_float()),
+        _ => (a, b)
+    }
+}
+
+fn compare<T: PartialOrd>(a: T, b: T, operation: &RelationOperations) -> bool {
+    match operation {
+        RelationOperations::Equal => a == b,
+        RelationOperations::NotEqual => a != b,
+        RelationOperations::Greater => a > b,
+        RelationOperations::GreaterEqual => a >= b,
+        RelationOperations::Less => a < b,
+        RelationOperations::LessEqual => a <= b
+    }
+}
+
+/// Возвращает привязку имени, которая была до входа в блок
+fn restore<T>(map: &mut HashMap<u64, T>, id: u64, old: Option<T>) {
+    match old {
+        Some(v) => map.insert(id, v),
+        None => map.remove(&id)
+    };
+}
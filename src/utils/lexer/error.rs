@@ -1,27 +1,25 @@
 use std::fmt::Display;
 
-use crate::utils::structs::tokens::TokenGroupLexer;
+use crate::utils::{messages::Message, structs::{span::Span, tokens::TokenGroupLexer}};
 
 pub type LexerResult<T> = Result<T, LexerError>;
 
+#[derive(Debug, Clone)]
 pub struct LexerError {
     pub path: String,
-    pub position: usize,
-    pub line: usize,
+    pub span: Span,
     pub token: TokenGroupLexer,
     pub message: String
 }
 
 impl Display for LexerError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "Лексический анализатор сообщает, что при анализе токена '{:?}' по адресу {}:{}:{} произошла ошибка: {}",
-            self.token,
-            self.path,
-            self.line,
-            self.position,
-            self.message
-        )
+        write!(f, "{}", Message::LexerFailedAt(
+            format!("{:?}", self.token),
+            self.path.clone(),
+            self.span.line,
+            self.span.column,
+            self.message.clone()
+        ))
     }
 }
\ No newline at end of file
@@ -1,61 +1,75 @@
-use std::{fs::File, sync::Arc};
+use crate::utils::{messages::Message, structs::{span::Span, tokens::{DelimitersGroup, DigitType, KeywordsGroup, TokenGroupLexer}}};
 
-use crate::utils::structs::tokens::{DelimitersGroup, DigitType, KeywordsGroup, TokenGroupLexer};
-
-mod error;
+pub mod error;
 use error::{LexerError, LexerResult};
 
+/// Лексический анализатор поверх исходного текста в памяти.
+/// Позиции считаются в байтах от начала текста, поэтому
+/// идентификаторы и числа берутся срезом без промежуточного буфера
 #[derive(Debug, Clone)]
-pub struct Lexer {
+pub struct Lexer<'a> {
     path: String,
+    source: &'a str,
+    /// Смещение текущего символа
     position: usize,
-    buffer: Vec<char>,
+    /// Смещение следующего символа
+    next_position: usize,
+    /// Смещение начала текущей лексемы
+    start: usize,
+    finished: bool,
 
-    line: usize,
-    inline_position: usize,
+    line: u32,
+    inline_position: u32,
 
-    character: char,
-    next_character: char,
-    input: Arc<File>
+    character: char
 }
 
-impl Lexer {
-    pub fn new(file: File, path: impl Into<String>) -> Self {
-        let file = Arc::new(file);
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str, path: impl Into<String>) -> Self {
         Self {
             path: path.into(),
+            source,
             position: 0,
-            buffer: Vec::new(),
+            next_position: 0,
+            start: 0,
+            finished: false,
 
             line: 1,
             inline_position: 0,
 
-            character: ' ',
-            next_character: read_char::read_next_char(&mut file.clone()).expect("Не удалось прочитать первый символ, возможно файл пустой"),
-            input: file
+            character: ' '
         }
     }
 
-    pub fn next_token(&mut self) -> LexerResult<(TokenGroupLexer, usize, usize)> {
+    pub fn next_token(&mut self) -> LexerResult<(TokenGroupLexer, Span)> {
         let res = self.skip_whitespace();
-        if let Err(e) = res {
-            return Err(e);
-        }
+        res?;
 
+        self.start = self.position;
         let line = self.line;
         let inline_position = self.inline_position;
 
         let token = match self.character {
+            _ if self.at_end() => TokenGroupLexer::Eof,
             '{' => TokenGroupLexer::Delimiters(DelimitersGroup::LeftCurlyBracket),
             '}' => TokenGroupLexer::Delimiters(DelimitersGroup::RightCurlyBracket),
             '(' => TokenGroupLexer::Delimiters(DelimitersGroup::LeftParenthesis),
             ')' => TokenGroupLexer::Delimiters(DelimitersGroup::RightParenthesis),
+            '[' => TokenGroupLexer::Delimiters(DelimitersGroup::LeftSquareBracket),
+            ']' => TokenGroupLexer::Delimiters(DelimitersGroup::RightSquareBracket),
             ',' => TokenGroupLexer::Delimiters(DelimitersGroup::Comma),
             ':' => TokenGroupLexer::Delimiters(DelimitersGroup::Colon),
             ';' => TokenGroupLexer::Delimiters(DelimitersGroup::Semicolon),
             '+' => TokenGroupLexer::Delimiters(DelimitersGroup::Plus),
             '-' => TokenGroupLexer::Delimiters(DelimitersGroup::Minus),
-            '*' => TokenGroupLexer::Delimiters(DelimitersGroup::Asterisk),
+            '*' => match self.next_char() {
+                '*' => {
+                    self.read_char();
+                    TokenGroupLexer::Delimiters(DelimitersGroup::Power)
+                },
+                _ => TokenGroupLexer::Delimiters(DelimitersGroup::Asterisk)
+            },
+            '^' => TokenGroupLexer::Delimiters(DelimitersGroup::Power),
             '/' => TokenGroupLexer::Delimiters(DelimitersGroup::Slash),
             '=' => match self.next_char() {
                 '=' => {
@@ -102,7 +116,7 @@ impl Lexer {
             'a'..='z'|'A'..='Z' => {
                 let identified = self.read_identifier();
                 return Ok((
-                    match identified.as_str() {
+                    match identified {
                         "var" => TokenGroupLexer::Keywords(KeywordsGroup::Var),
                         "let" => TokenGroupLexer::Keywords(KeywordsGroup::Let),
                         "if" => TokenGroupLexer::Keywords(KeywordsGroup::If),
@@ -115,6 +129,18 @@ impl Lexer {
                         "loop" => TokenGroupLexer::Keywords(KeywordsGroup::Loop),
                         "input" => TokenGroupLexer::Keywords(KeywordsGroup::Input),
                         "output" => TokenGroupLexer::Keywords(KeywordsGroup::Output),
+                        "break" => TokenGroupLexer::Keywords(KeywordsGroup::Break),
+                        "continue" => TokenGroupLexer::Keywords(KeywordsGroup::Continue),
+                        "proc" => TokenGroupLexer::Keywords(KeywordsGroup::Proc),
+                        "func" => TokenGroupLexer::Keywords(KeywordsGroup::Func),
+                        "return" => TokenGroupLexer::Keywords(KeywordsGroup::Return),
+                        "array" => TokenGroupLexer::Keywords(KeywordsGroup::Array),
+                        "of" => TokenGroupLexer::Keywords(KeywordsGroup::Of),
+                        "mod" => TokenGroupLexer::Keywords(KeywordsGroup::Mod),
+                        "div" => TokenGroupLexer::Keywords(KeywordsGroup::Div),
+                        "const" => TokenGroupLexer::Keywords(KeywordsGroup::Const),
+                        "case" => TokenGroupLexer::Keywords(KeywordsGroup::Case),
+                        "end_case" => TokenGroupLexer::Keywords(KeywordsGroup::EndCase),
                         "integer" => TokenGroupLexer::Keywords(KeywordsGroup::Integer),
                         "real" => TokenGroupLexer::Keywords(KeywordsGroup::Real),
                         "boolean" => TokenGroupLexer::Keywords(KeywordsGroup::Boolean),
@@ -122,19 +148,17 @@ impl Lexer {
                         "false" => TokenGroupLexer::Keywords(KeywordsGroup::False),
                         name if name.contains('_') => return Err(LexerError {
                             path: self.path.clone(),
-                            position: inline_position,
-                            line: self.line,
+                            span: self.span_from(line, inline_position),
                             token: TokenGroupLexer::Identifier(name.into()),
-                            message: "встречен символ '_'".into()
+                            message: Message::Underscore.to_string()
                         }),
                         name => TokenGroupLexer::Identifier(name.into())
                     },
-                    line,
-                    inline_position
+                    self.span_from(line, inline_position)
                 ));
             },
             '0'..='9'|'.' => return self.read_digit(),
-            '\0' => TokenGroupLexer::Eof,
+            '"' => return self.read_string(),
             _ => TokenGroupLexer::Illegal((
                 self.character,
                 self.line,
@@ -144,47 +168,52 @@ impl Lexer {
 
         self.read_char();
 
-        Ok((token, line, inline_position))
+        Ok((token, self.span_from(line, inline_position)))
+    }
+
+    fn span_from(&self, line: u32, column: u32) -> Span {
+        Span::new(line, column, self.line, self.inline_position)
+            .with_offsets(self.start, self.position)
+    }
+
+    /// Конец текста определяется по смещению: символ `\0` в середине
+    /// текста - обычный недопустимый символ
+    fn at_end(&self) -> bool {
+        self.position >= self.source.len()
     }
 
     fn read_char(&mut self) {
-        self.character = self.next_character;
-        match read_char::read_next_char(&mut self.input.clone()) {
-            Ok(v) => self.next_character = v,
-            Err(_) => self.next_character = '\0'
+        self.position = self.next_position;
+        self.character = self.source[self.position..].chars().next().unwrap_or('\0');
+        if self.position < self.source.len() {
+            self.next_position += self.character.len_utf8();
         }
 
-        self.position += 1;
         self.inline_position += 1;
     }
 
     fn next_char(&self) -> char {
-        self.next_character
+        self.source[self.next_position..].chars().next().unwrap_or('\0')
     }
 
-    fn prev_char(&self) -> char {
-        match self.buffer.get(self.buffer.len()-1) {
-            Some(v) => *v,
-            None => ' '
-        }
-        
+    /// Уже прочитанная часть текущей лексемы
+    fn lexeme(&self) -> &'a str {
+        &self.source[self.start..self.position]
     }
 
-    fn read_identifier(&mut self) -> String {
+    fn prev_char(&self) -> char {
+        self.lexeme().chars().next_back().unwrap_or(' ')
+    }
 
+    fn read_identifier(&mut self) -> &'a str {
         while self.character.is_ascii_alphanumeric() || self.character == '_' {
-            self.buffer.push(self.character);
             self.read_char();
         }
 
-        let res = String::from_iter(&self.buffer);
-
-        self.buffer.clear();
-
-        return res;
+        self.lexeme()
     }
 
-    fn read_digit(&mut self) -> LexerResult<(TokenGroupLexer, usize, usize)> {
+    fn read_digit(&mut self) -> LexerResult<(TokenGroupLexer, Span)> {
         let line = self.line;
         let inline_position = self.inline_position;
 
@@ -207,14 +236,13 @@ impl Lexer {
                         if digit_type.clone() >> DigitType::Hex {
                             digit_type = DigitType::Hex;
                         } else {
-                            return self.variable_error("шестнадцатеричное число");
+                            return self.variable_error(Message::HexNumber);
                         },
                     _ => if digit_type.clone() >> DigitType::Binary {
-                        self.buffer.push(self.character);
                         self.read_char();
                         break;
                     } else {
-                        return self.variable_error("двоичное число");
+                        return self.variable_error(Message::BinaryNumber);
                     }
                 },
                 'D'|'d' => match self.next_char() {
@@ -222,14 +250,13 @@ impl Lexer {
                         if digit_type.clone() >> DigitType::Hex {
                             digit_type = DigitType::Hex;
                         } else {
-                            return self.variable_error("шестнадцатеричное число");
+                            return self.variable_error(Message::HexNumber);
                         },
                     _ => if digit_type.clone() >> DigitType::Digital {
-                        self.buffer.push(self.character);
                         self.read_char();
                         break;
                     } else {
-                        return self.variable_error("десятичное число");
+                        return self.variable_error(Message::DecimalNumber);
                     }
                 },
                 'E'|'e' =>
@@ -238,44 +265,43 @@ impl Lexer {
                             digit_type = DigitType::HexPoint;
                         }
                         if exp {
-                            return self.variable_error("число с плавающей точкой, обнаружен второй символ 'E'");
+                            return self.variable_error(Message::FloatSecondExponent);
                         }
                         exp = true;
                     } else {
-                        return self.variable_error("число с плавающей точкой");
+                        return self.variable_error(Message::FloatNumber);
                     },
                 'H'|'h' => match self.next_char() {
                     '0'..='9'|'a'..='f'|'A'..='F'|'.'|'h'|'H' =>
-                        return self.variable_error("шестнадцатеричное число, так как обнаружен не поддерживаемый символ 'H' или 'h'"),
+                        return self.variable_error(Message::HexUnsupportedSuffix),
                     _ => if digit_type.clone() >> DigitType::Hex {
-                        self.buffer.push(self.character);
                         self.read_char();
                         break;
                     } else {
-                        return self.variable_error("шестнадцатеричное число");
+                        return self.variable_error(Message::HexNumber);
                     }
                 },
                 '.' => if digit_type.clone() >> DigitType::Point {
                     digit_type = DigitType::Point;
                     if exp {
-                        return self.variable_error("число с плавающей точкой, неожиданная точка в экспоненте");
+                        return self.variable_error(Message::FloatPointInExponent);
                     }
                     match self.next_char() {
                         '0'..='9' => (),
-                        _ => return self.variable_error("число с плавающей точкой, неожиданная точка без цифр после неё")
+                        _ => return self.variable_error(Message::FloatPointWithoutDigits)
                     }
                 } else {
-                    return self.variable_error("число с плавающей точкой");
+                    return self.variable_error(Message::FloatNumber);
                 },
                 'O'|'o' => match self.next_char() {
                     '0'..='9'|'a'..='f'|'A'..='F'|'.'|'h'|'H' => {
-                        return self.variable_error("восьмеричное число, так как дальше обнаружен не поддерживаемый символ");
+                        return self.variable_error(Message::OctalUnsupportedSuffix);
                     },
                     _ => if digit_type.clone() >> DigitType::Octal {
-                        self.next_char();
+                        self.read_char();
                         break;
                     } else {
-                        return self.variable_error("восьмеричное число");
+                        return self.variable_error(Message::OctalNumber);
                     }
                 },
                 '+'|'-' => if exp {
@@ -283,15 +309,12 @@ impl Lexer {
                         'E'|'e' => if digit_type.clone() >> DigitType::Point {
                             digit_type = DigitType::Point;
                         } else {
-                            return self.variable_error("число с плавающей точкой");
+                            return self.variable_error(Message::FloatNumber);
                         },
                         _ => {
-                            let res = String::from_iter(&self.buffer);
-                            self.buffer.clear();
-                            return Ok(
-                                (TokenGroupLexer::Variables(res),
-                                line,
-                                inline_position
+                            return Ok((
+                                TokenGroupLexer::Variables(self.lexeme().into()),
+                                self.span_from(line, inline_position)
                             ))
                         }
                     }
@@ -301,46 +324,89 @@ impl Lexer {
                 'A'..='Z'|'a'..='z' => if digit_type.clone() >> DigitType::Hex {
                     digit_type = DigitType::Hex;
                 } else {
-                    return self.variable_error("шестнадцатеричное число");
+                    return self.variable_error(Message::HexNumber);
                 },
                 _ => if digit_type.clone() >> DigitType::Digital {
                     break;
                 } else if digit_type.clone() >> DigitType::Point {
                     match self.prev_char() {
                         '0'..='9' => break,
-                        _ => return self.variable_error("число с плавающей точкой"),
+                        _ => return self.variable_error(Message::FloatNumber),
                     }
                 } else {
-                    return self.variable_error("десятичное число или число с плавающей точкой");
+                    return self.variable_error(Message::DecimalOrFloatNumber);
                 }
             };
-            self.buffer.push(self.character);
             self.read_char();
         };
 
-        let res = String::from_iter(&self.buffer);
-        self.buffer.clear();
-
         Ok((
-            TokenGroupLexer::Variables(res),
-            line,
-            inline_position
+            TokenGroupLexer::Variables(self.lexeme().into()),
+            self.span_from(line, inline_position)
         ))
     }
 
-    fn variable_error<T>(&mut self, message: impl Into<String>) -> LexerResult<T> {
-        let res = String::from_iter(&self.buffer);
-        self.buffer.clear();
+    /// Строка в двойных кавычках. Поддерживаются `\n`, `\t`, `\\` и `\"`,
+    /// перевод строки внутри строки не допускается
+    fn read_string(&mut self) -> LexerResult<(TokenGroupLexer, Span)> {
+        let line = self.line;
+        let inline_position = self.inline_position;
+        let mut value = String::new();
+
+        self.read_char();
+        loop {
+            match self.character {
+                '"' => break,
+                '\n' => return self.unterminated(value, line, inline_position),
+                _ if self.at_end() => return self.unterminated(value, line, inline_position),
+                '\\' => {
+                    let start = self.position;
+                    self.read_char();
+                    value.push(match self.character {
+                        'n' => '\n',
+                        't' => '\t',
+                        '\\' => '\\',
+                        '"' => '"',
+                        _ if self.at_end() => return self.unterminated(value, line, inline_position),
+                        character => return Err(LexerError {
+                            path: self.path.clone(),
+                            span: Span::new(self.line, self.inline_position - 1, self.line, self.inline_position + 1)
+                                .with_offsets(start, self.next_position),
+                            token: TokenGroupLexer::String(value),
+                            message: Message::InvalidEscape(character).to_string()
+                        })
+                    });
+                },
+                character => value.push(character)
+            }
+            self.read_char();
+        }
+        self.read_char();
+
+        Ok((TokenGroupLexer::String(value), self.span_from(line, inline_position)))
+    }
+
+    fn unterminated<T>(&self, value: String, line: u32, inline_position: u32) -> LexerResult<T> {
         Err(LexerError {
             path: self.path.clone(),
-            position: self.inline_position,
-            line: self.line,
+            span: self.span_from(line, inline_position),
+            token: TokenGroupLexer::String(value),
+            message: Message::UnterminatedString.to_string()
+        })
+    }
+
+    fn variable_error<T>(&mut self, message: Message) -> LexerResult<T> {
+        let res = self.lexeme();
+        Err(LexerError {
+            path: self.path.clone(),
+            span: Span::new(
+                self.line,
+                self.inline_position - res.chars().count() as u32,
+                self.line,
+                self.inline_position + 1
+            ).with_offsets(self.start, self.next_position),
             token: TokenGroupLexer::Variables(String::new()),
-            message: format!(
-                "Не возможно интерпретировать '{}' как {}",
-                res,
-                message.into()
-            )
+            message: Message::CannotInterpret(res.into(), message.to_string()).to_string()
         })
     }
 
@@ -352,21 +418,22 @@ impl Lexer {
                 true
             },
             '%' => {
+                let (line, inline_position, start) = (self.line, self.inline_position, self.position);
                 self.read_char();
-                while self.character != '%' && self.character != '\0' {
+                while self.character != '%' && !self.at_end() {
                     if self.character == '\n' {
                         self.line += 1;
                         self.inline_position = 0;
                     }
                     self.read_char();
                 };
-                if self.character == '\0' {
+                if self.at_end() {
                     return Err(LexerError {
                         path: self.path.clone(),
-                        position: self.inline_position,
-                        line: self.line,
+                        span: Span::new(line, inline_position, line, inline_position + 1)
+                            .with_offsets(start, start + 1),
                         token: TokenGroupLexer::Illegal((self.character, self.line, self.inline_position)),
-                        message: String::from("Неожиданный конец файла")
+                        message: Message::UnexpectedEof.to_string()
                     });
                 };
                 true
@@ -378,4 +445,20 @@ impl Lexer {
         Ok(())
     }
 
+}
+
+/// Выдаёт лексемы до конца файла включительно,
+/// после первой ошибки итерация прекращается
+impl Iterator for Lexer<'_> {
+    type Item = LexerResult<(TokenGroupLexer, Span)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let res = self.next_token();
+        self.finished = !matches!(res, Ok((ref token, _)) if *token != TokenGroupLexer::Eof);
+        Some(res)
+    }
 }
\ No newline at end of file
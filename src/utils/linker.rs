@@ -0,0 +1,32 @@
+use std::{env, fs, path::PathBuf, process::Command, sync::atomic::{AtomicUsize, Ordering}};
+
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Уникальный путь во временном каталоге
+pub fn temp_path(suffix: &str) -> PathBuf {
+    let id = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    env::temp_dir().join(format!("calamity-{}-{}{}", std::process::id(), id, suffix))
+}
+
+/// Компонует объектный файл с libc в исполняемый файл системным
+/// компилятором C (`cc` или переменная окружения `CC`). Объект использует
+/// абсолютные перемещения, поэтому исполняемый файл собирается без PIE.
+/// libm нужна для `pow` в вещественном возведении в степень
+pub fn link(object: &[u8], output: &str) -> Result<(), String> {
+    let object_path = temp_path(".o");
+    fs::write(&object_path, object).map_err(|e| e.to_string())?;
+
+    let cc = env::var("CC").unwrap_or_else(|_| String::from("cc"));
+    let res = Command::new(&cc)
+        .args(["-no-pie", "-z", "noexecstack", "-o", output])
+        .arg(&object_path)
+        .arg("-lm")
+        .output();
+    let _ = fs::remove_file(&object_path);
+
+    match res {
+        Ok(res) if res.status.success() => Ok(()),
+        Ok(res) => Err(String::from_utf8_lossy(&res.stderr).trim().to_string()),
+        Err(e) => Err(format!("{}: {}", cc, e))
+    }
+}
@@ -0,0 +1,673 @@
+use std::{cell::Cell, env, fmt::Display, str::FromStr};
+
+use super::semantic::lint::Lint;
+
+/// Язык сообщений компилятора
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    Ru,
+    En
+}
+
+impl Lang {
+    /// Язык по переменной окружения `LANG`: русский для `ru*`, иначе английский
+    pub fn from_env() -> Self {
+        match env::var("LANG") {
+            Ok(v) if v.to_lowercase().starts_with("ru") => Lang::Ru,
+            _ => Lang::En
+        }
+    }
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ru" => Ok(Lang::Ru),
+            "en" => Ok(Lang::En),
+            _ => Err(Message::UnknownLang(s.into()).to_string())
+        }
+    }
+}
+
+thread_local! {
+    static LANG: Cell<Lang> = const { Cell::new(Lang::Ru) };
+}
+
+/// Устанавливает язык сообщений текущего потока.
+/// Библиотека по умолчанию говорит по-русски и окружение не читает
+pub fn set_lang(lang: Lang) {
+    LANG.with(|v| v.set(lang));
+}
+
+pub fn lang() -> Lang {
+    LANG.with(|v| v.get())
+}
+
+/// Выбирает один из двух вариантов текста по текущему языку
+pub fn tr(ru: &'static str, en: &'static str) -> &'static str {
+    match lang() {
+        Lang::Ru => ru,
+        Lang::En => en
+    }
+}
+
+fn lint_names() -> String {
+    Lint::ALL.map(|lint| lint.name()).join(", ")
+}
+
+/// Каталог сообщений компилятора
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    // командная строка
+    Help,
+    UnknownLang(String),
+    MissingOutputFile,
+    InvalidArguments,
+    MissingInputFile,
+    CannotOpenFile(String, String),
+    CannotWriteObject(String),
+    LexerDone,
+    SyntaxDone,
+    SemanticDone,
+    ObjectDone,
+    TableHeader,
+    Error,
+    Warning,
+    Note,
+    UnknownErrorFormat(String),
+    OutputForSeveralFiles,
+    UnknownEmit(String),
+    UnknownCommand(String),
+    UnknownLint(String),
+    ExecutableDone,
+    LinkFailed(String),
+    RunFailed(String),
+    KilledBySignal(String),
+
+    // лексический анализ
+    UnexpectedCharacter(char),
+    NumberOverflow(String),
+    LexerFailed(String, String),
+    LexerFailedAt(String, String, u32, u32, String),
+    Underscore,
+    UnexpectedEof,
+    UnterminatedString,
+    InvalidEscape(char),
+    CannotInterpret(String, String),
+    BinaryNumber,
+    OctalNumber,
+    OctalUnsupportedSuffix,
+    DecimalNumber,
+    HexNumber,
+    HexUnsupportedSuffix,
+    FloatNumber,
+    FloatSecondExponent,
+    FloatPointInExponent,
+    FloatPointWithoutDigits,
+    DecimalOrFloatNumber,
+
+    // синтаксический анализ
+    FoundToken(String, String),
+    UnexpectedToken(String),
+    ProgramStart,
+    ExpectedEndOfProgram,
+    ExpectedDescriptionOrOperator,
+    ExpectedSemicolon,
+    ExpectedRightCurlyBracket,
+    ExpectedLeftParenthesis,
+    ExpectedRightParenthesis,
+    ExpectedIdentifier,
+    ExpectedIdentifierOrSemicolon,
+    ExpectedIdentifierOrRightParenthesis,
+    ExpectedColonOrComma,
+    ExpectedType,
+    ExpectedOperator,
+    ExpectedOperand,
+    ExpectedEqual,
+    ExpectedThen,
+    ExpectedEndElse,
+    ExpectedEndCase,
+    ExpectedWhile,
+    ExpectedLoop,
+    ExpectedSemicolonOrExpression,
+    ExpectedLeftCurlyBracket,
+    ExpectedColon,
+    ExpectedCommaOrRightParenthesis,
+    ExpectedSemicolonOrRightParenthesis,
+    NestedFunction,
+    ForClauses,
+    ExpectedLeftSquareBracket,
+    ExpectedRightSquareBracket,
+    ExpectedOf,
+    ArraySize,
+    ArrayTooLarge(i64),
+
+    // семантический анализ
+    AssignError(String, String),
+    InvalidOperation(String, String),
+    NotDefined(String),
+    TypeError(String, String),
+    NotBoolean(String),
+    AlreadyDeclared(String),
+    Redeclaration,
+    FirstDeclaration,
+    Unary,
+    UnaryMinus,
+    Power,
+    Conversion(String),
+    OutsideLoop(String),
+    OutsideFunction,
+    NoResult(String),
+    ResultRequired(String),
+    ArgumentCount(String, usize, usize),
+    DeclaredHere,
+    NotArray(String),
+    ArrayUsage(String),
+    IndexType(String),
+    OuterScope(String, u32),
+    ConstantChange(String),
+    NotConstant,
+    NonConstant,
+    CaseType(String),
+    DuplicateLabel(i64),
+    FirstLabel,
+    MaybeUninitialized(String),
+    UnusedVariable(String),
+    UnusedAssignment(String),
+    UnusedInput(String),
+    InputHere,
+    ConstantCondition,
+    InfiniteLoop,
+    AlwaysTrue,
+    UnreachableCode,
+    LintDefault(&'static str),
+    LintDenied(&'static str),
+    UnassignedIf(String, bool),
+    UnassignedLoop(String),
+    UnassignedBreak(String),
+    UnassignedArm(String),
+    UnassignedNoLabel(String),
+    StorageSize,
+
+    // объектный файл
+    ObjectFailed(String),
+
+    // выполнение
+    DivisionError,
+    Divisor,
+    RuntimeIo(String),
+    IndexOutOfBounds(String, String),
+    ArrayAllocation(String)
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ru = lang() == Lang::Ru;
+        match self {
+            Message::Help => f.write_str(tr(
+"Использование: calamity-script [команда] [аргументы] файлы...
+Файл '-' читается со стандартного ввода. Файл с именем команды
+указывается как ./lex или после --.
+
+Команды:
+\tlex         - остановиться после лексического анализа
+\tparse       - остановиться после синтаксического анализа
+\tcheck       - остановиться после семантического анализа
+\tbuild       - создать исполняемый файл (по умолчанию)
+\trun         - выполнить программу интерпретатором
+
+Аргументы:
+\t-h | --help - отобразит текущее сообщение
+\t--          - остальные аргументы - входные файлы
+\t-lo         - Выводит лексемы в виде объектов
+\t-c          - Компактный режим
+\t-o          - имя выходного файла
+\t--emit=exe|obj - создать исполняемый (по умолчанию) или объектный файл
+\t--native    - run: собрать и запустить машинный код
+\t--lang      - язык сообщений: ru или en
+\t--error-format=human|json - формат вывода ошибок в stderr
+\t-W | -A | -D имя - выдавать, не выдавать предупреждение или считать его ошибкой
+\t--deny-warnings - считать ошибками все предупреждения
+\t-O0 | -O1   - без оптимизаций (по умолчанию) или со свёрткой констант
+\t              и удалением недостижимых ветвей
+
+Коды завершения: 1 - неверные аргументы, 2 - ошибка ввода-вывода,
+3 - лексическая, 4 - синтаксическая, 5 - семантическая ошибка,
+6 - ошибка создания объектного файла, 7 - ошибка компоновки,
+8 - ошибка выполнения, в том числе в собранной программе при
+run --native: её код завершения передаётся как есть, а завершение
+сигналом считается ошибкой выполнения",
+"Usage: calamity-script [command] [options] files...
+The file '-' is read from standard input. A file named like a command
+is given as ./lex or after --.
+
+Commands:
+\tlex         - stop after lexical analysis
+\tparse       - stop after syntax analysis
+\tcheck       - stop after semantic analysis
+\tbuild       - create an executable (default)
+\trun         - run the program with the interpreter
+
+Options:
+\t-h | --help - show this message
+\t--          - treat the remaining arguments as files
+\t-lo         - print tokens as objects
+\t-c          - compact mode
+\t-o          - output file name
+\t--emit=exe|obj - create an executable (default) or an object file
+\t--native    - run: build and run native code instead
+\t--lang      - message language: ru or en
+\t--error-format=human|json - format of diagnostics printed to stderr
+\t-W | -A | -D name - warn about, allow or deny a lint
+\t--deny-warnings - treat all warnings as errors
+\t-O0 | -O1   - no optimisations (default) or constant folding
+\t              and dead branch elimination
+
+Exit codes: 1 - invalid arguments, 2 - I/O error,
+3 - lexical, 4 - syntax, 5 - semantic error,
+6 - object file error, 7 - link error,
+8 - runtime error, also in the built program under run --native:
+its exit code is passed through, and death by a signal counts
+as a runtime error")),
+            Message::UnknownLang(v) => if ru {
+                write!(f, "Неизвестный язык '{}', доступны ru и en", v)
+            } else {
+                write!(f, "Unknown language '{}', expected ru or en", v)
+            },
+            Message::OutputForSeveralFiles => f.write_str(tr(
+                "Выходной файл нельзя указать для нескольких входных файлов",
+                "An output file cannot be given for several input files"
+            )),
+            Message::UnknownEmit(v) => if ru {
+                write!(f, "Неизвестный вид выходного файла '{}', доступны exe и obj", v)
+            } else {
+                write!(f, "Unknown output kind '{}', expected exe or obj", v)
+            },
+            Message::UnknownCommand(v) => if ru {
+                write!(f, "Неизвестная команда '{}', доступны lex, parse, check, build и run", v)
+            } else {
+                write!(f, "Unknown command '{}', expected lex, parse, check, build or run", v)
+            },
+            Message::UnknownLint(v) => if ru {
+                write!(f, "Неизвестное предупреждение '{}', доступны: {}", v, lint_names())
+            } else {
+                write!(f, "Unknown lint '{}', expected one of: {}", v, lint_names())
+            },
+            Message::ExecutableDone => f.write_str(tr("Создание исполняемого файла успешно выполнено.", "Executable created successfully.")),
+            Message::LinkFailed(e) => if ru {
+                write!(f, "Не удалось скомпоновать программу: {}", e)
+            } else {
+                write!(f, "Failed to link the program: {}", e)
+            },
+            Message::RunFailed(e) => if ru {
+                write!(f, "Не удалось запустить программу: {}", e)
+            } else {
+                write!(f, "Failed to run the program: {}", e)
+            },
+            Message::KilledBySignal(v) => if ru {
+                write!(f, "Программа завершена сигналом {}", v)
+            } else {
+                write!(f, "The program was terminated by signal {}", v)
+            },
+            Message::MissingOutputFile => f.write_str(tr("Не указан выходной файл", "No output file specified")),
+            Message::InvalidArguments => f.write_str(tr(
+                "Неверные аргументы, доступные аргументы можно увидеть введя: --help",
+                "Invalid arguments, available arguments can be listed with: --help"
+            )),
+            Message::MissingInputFile => f.write_str(tr("Не указан входной файл", "No file specified")),
+            Message::CannotOpenFile(path, e) => if ru {
+                write!(f, "Не удалось открыть файл {}: {}", path, e)
+            } else {
+                write!(f, "Cannot open file {}: {}", path, e)
+            },
+            Message::CannotWriteObject(e) => if ru {
+                write!(f, "Не удалось записать объектный файл: {}", e)
+            } else {
+                write!(f, "Cannot write object file: {}", e)
+            },
+            Message::LexerDone => f.write_str(tr("Лексический анализ успешно выполнен.", "Lexical analysis completed successfully.")),
+            Message::SyntaxDone => f.write_str(tr("Синтаксический анализ успешно выполнен.", "Syntax analysis completed successfully.")),
+            Message::SemanticDone => f.write_str(tr("Семантический анализ успешно выполнен.", "Semantic analysis completed successfully.")),
+            Message::ObjectDone => f.write_str(tr("Создание объектного файла успешно выполнено.", "Object file created successfully.")),
+            Message::TableHeader => f.write_str(tr(
+                "|        Переменные        |           Числа          |",
+                "|       Identifiers        |          Numbers         |"
+            )),
+            Message::Error => f.write_str(tr("ошибка", "error")),
+            Message::Warning => f.write_str(tr("предупреждение", "warning")),
+            Message::Note => f.write_str(tr("примечание", "note")),
+            Message::UnknownErrorFormat(v) => if ru {
+                write!(f, "Неизвестный формат ошибок '{}', доступны human и json", v)
+            } else {
+                write!(f, "Unknown error format '{}', expected human or json", v)
+            },
+
+            Message::UnexpectedCharacter(c) => if ru {
+                write!(f, "Обнаружен непредвиденный символ: {}", c.escape_debug())
+            } else {
+                write!(f, "Unexpected character: {}", c.escape_debug())
+            },
+            Message::NumberOverflow(literal) => if ru {
+                write!(f, "Число {} не помещается в 64-битное целое", literal)
+            } else {
+                write!(f, "Number {} does not fit in a 64-bit integer", literal)
+            },
+            Message::LexerFailed(token, message) => if ru {
+                write!(f, "Лексический анализатор сообщает, что при анализе токена '{}' произошла ошибка: {}", token, message)
+            } else {
+                write!(f, "Lexer failed to analyse token '{}': {}", token, message)
+            },
+            Message::LexerFailedAt(token, path, line, column, message) => if ru {
+                write!(f, "Лексический анализатор сообщает, что при анализе токена '{}' по адресу {}:{}:{} произошла ошибка: {}", token, path, line, column, message)
+            } else {
+                write!(f, "Lexer failed to analyse token '{}' at {}:{}:{}: {}", token, path, line, column, message)
+            },
+            Message::Underscore => f.write_str(tr("встречен символ '_'", "found character '_'")),
+            Message::UnexpectedEof => f.write_str(tr("Неожиданный конец файла", "Unexpected end of file")),
+            Message::UnterminatedString => f.write_str(tr("Строка не закрыта до конца строки", "Unterminated string literal")),
+            Message::InvalidEscape(c) => if ru {
+                write!(f, "Неизвестная управляющая последовательность '\\{}'", c.escape_default())
+            } else {
+                write!(f, "Unknown escape sequence '\\{}'", c.escape_default())
+            },
+            Message::CannotInterpret(value, kind) => if ru {
+                write!(f, "Не возможно интерпретировать '{}' как {}", value, kind)
+            } else {
+                write!(f, "Cannot interpret '{}' as {}", value, kind)
+            },
+            Message::BinaryNumber => f.write_str(tr("двоичное число", "a binary number")),
+            Message::OctalNumber => f.write_str(tr("восьмеричное число", "an octal number")),
+            Message::OctalUnsupportedSuffix => f.write_str(tr(
+                "восьмеричное число, так как дальше обнаружен не поддерживаемый символ",
+                "an octal number, an unsupported character follows"
+            )),
+            Message::DecimalNumber => f.write_str(tr("десятичное число", "a decimal number")),
+            Message::HexNumber => f.write_str(tr("шестнадцатеричное число", "a hexadecimal number")),
+            Message::HexUnsupportedSuffix => f.write_str(tr(
+                "шестнадцатеричное число, так как обнаружен не поддерживаемый символ 'H' или 'h'",
+                "a hexadecimal number, found unsupported character 'H' or 'h'"
+            )),
+            Message::FloatNumber => f.write_str(tr("число с плавающей точкой", "a floating point number")),
+            Message::FloatSecondExponent => f.write_str(tr(
+                "число с плавающей точкой, обнаружен второй символ 'E'",
+                "a floating point number, found a second 'E'"
+            )),
+            Message::FloatPointInExponent => f.write_str(tr(
+                "число с плавающей точкой, неожиданная точка в экспоненте",
+                "a floating point number, unexpected point in the exponent"
+            )),
+            Message::FloatPointWithoutDigits => f.write_str(tr(
+                "число с плавающей точкой, неожиданная точка без цифр после неё",
+                "a floating point number, no digits after the point"
+            )),
+            Message::DecimalOrFloatNumber => f.write_str(tr(
+                "десятичное число или число с плавающей точкой",
+                "a decimal or floating point number"
+            )),
+
+            Message::FoundToken(text, token) => if ru {
+                write!(f, "{}, а встречена лексема: {}", text, token)
+            } else {
+                write!(f, "{}, found token: {}", text, token)
+            },
+            Message::UnexpectedToken(token) => if ru {
+                write!(f, "Встречена непредвиденная лексема {}", token)
+            } else {
+                write!(f, "Unexpected token {}", token)
+            },
+            Message::ProgramStart => f.write_str(tr("Программа должна начинаться с '{'", "Program must start with '{'")),
+            Message::ExpectedEndOfProgram => f.write_str(tr("Ожидался конец программы", "Expected end of program")),
+            Message::ExpectedDescriptionOrOperator => f.write_str(tr("Ожидалось описание или оператор", "Expected declaration or operator")),
+            Message::ExpectedSemicolon => f.write_str(tr("Ожидалась ';'", "Expected ';'")),
+            Message::ExpectedRightCurlyBracket => f.write_str(tr("Ожидалось '}'", "Expected '}'")),
+            Message::ExpectedLeftParenthesis => f.write_str(tr("Ожидалась '('", "Expected '('")),
+            Message::ExpectedRightParenthesis => f.write_str(tr("Ожидалась ')'", "Expected ')'")),
+            Message::ExpectedIdentifier => f.write_str(tr("Ожидался идентификатор", "Expected identifier")),
+            Message::ExpectedIdentifierOrSemicolon => f.write_str(tr("Ожидался идентификатор или ';'", "Expected identifier or ';'")),
+            Message::ExpectedIdentifierOrRightParenthesis => f.write_str(tr("Ожидался идентификатор или ')'", "Expected identifier or ')'")),
+            Message::ExpectedColonOrComma => f.write_str(tr("Ожидалось ':' или ','", "Expected ':' or ','")),
+            Message::ExpectedType => f.write_str(tr("Ожидался тип данных", "Expected data type")),
+            Message::ExpectedOperator => f.write_str(tr("Ожидался оператор", "Expected operator")),
+            Message::ExpectedOperand => f.write_str(tr("Ожидался операнд", "Expected operand")),
+            Message::ExpectedEqual => f.write_str(tr("Ожидался знак '='", "Expected '='")),
+            Message::ExpectedThen => f.write_str(tr("Ожидалось 'then'", "Expected 'then'")),
+            Message::ExpectedEndElse => f.write_str(tr("Ожидалось 'end_else'", "Expected 'end_else'")),
+            Message::ExpectedEndCase => f.write_str(tr("Ожидалось 'end_case'", "Expected 'end_case'")),
+            Message::ExpectedWhile => f.write_str(tr("Ожидалось ключевое слово 'while'", "Expected keyword 'while'")),
+            Message::ExpectedLoop => f.write_str(tr("Ожидалась 'loop'", "Expected 'loop'")),
+            Message::ExpectedSemicolonOrExpression => f.write_str(tr("Ожидалась ';' или выражение", "Expected ';' or expression")),
+            Message::ExpectedLeftCurlyBracket => f.write_str(tr("Ожидалось '{'", "Expected '{'")),
+            Message::ExpectedColon => f.write_str(tr("Ожидалось ':'", "Expected ':'")),
+            Message::ExpectedCommaOrRightParenthesis => f.write_str(tr("Ожидалась ',' или ')'", "Expected ',' or ')'")),
+            Message::ExpectedSemicolonOrRightParenthesis => f.write_str(tr("Ожидалась ';' или ')'", "Expected ';' or ')'")),
+            Message::ExpectedLeftSquareBracket => f.write_str(tr("Ожидалась '['", "Expected '['")),
+            Message::ExpectedRightSquareBracket => f.write_str(tr("Ожидалась ']'", "Expected ']'")),
+            Message::ExpectedOf => f.write_str(tr("Ожидалось 'of'", "Expected 'of'")),
+            Message::ArraySize => f.write_str(tr(
+                "Размер массива должен быть положительным целым числом",
+                "Array size must be a positive integer"
+            )),
+            Message::ArrayTooLarge(limit) => if ru {
+                write!(f, "Размер массива не может превышать {} элементов", limit)
+            } else {
+                write!(f, "Array size cannot exceed {} elements", limit)
+            },
+            Message::ForClauses => f.write_str(tr(
+                "В заголовке for нельзя смешивать условия и присваивания",
+                "Cannot mix conditions and assignments in a for header"
+            )),
+            Message::NestedFunction => f.write_str(tr(
+                "Процедуры и функции объявляются только в основном блоке программы",
+                "Procedures and functions can only be declared in the main program block"
+            )),
+
+            Message::AssignError(from, to) => if ru {
+                write!(f, "Не удалось присвоить тип {} к {}", from, to)
+            } else {
+                write!(f, "Cannot assign type {} to {}", from, to)
+            },
+            Message::InvalidOperation(operation, t) => if ru {
+                write!(f, "Невозможно выполнить операцию {} над типом {}", operation, t)
+            } else {
+                write!(f, "Cannot apply operation {} to type {}", operation, t)
+            },
+            Message::NotDefined(name) => if ru {
+                write!(f, "Переменная {} ещё не объявлена или не инициализирована", name)
+            } else {
+                write!(f, "Variable {} is not declared or not initialized", name)
+            },
+            Message::TypeError(first, second) => if ru {
+                write!(f, "Ошибка типов: невозможно выполнить операцию с {} и {}", first, second)
+            } else {
+                write!(f, "Type error: cannot apply operation to {} and {}", first, second)
+            },
+            Message::NotBoolean(t) => if ru {
+                write!(f, "В условии обнаружен недопустимый тип {}", t)
+            } else {
+                write!(f, "Invalid type {} in condition", t)
+            },
+            Message::AlreadyDeclared(name) => if ru {
+                write!(f, "Переменная {} уже объявлена", name)
+            } else {
+                write!(f, "Variable {} is already declared", name)
+            },
+            Message::Redeclaration => f.write_str(tr("повторное объявление", "redeclared here")),
+            Message::FirstDeclaration => f.write_str(tr("первое объявление", "first declared here")),
+            Message::Unary => f.write_str(tr("унарная", "unary")),
+            Message::UnaryMinus => f.write_str(tr("унарный минус", "unary minus")),
+            Message::Power => f.write_str(tr("возведение в степень", "exponentiation")),
+            Message::Conversion(t) => if ru {
+                write!(f, "преобразование в {}", t)
+            } else {
+                write!(f, "conversion to {}", t)
+            },
+            Message::OutsideFunction => f.write_str(tr(
+                "Оператор return используется вне процедуры или функции",
+                "`return` used outside of a procedure or function"
+            )),
+            Message::NoResult(name) => if ru {
+                write!(f, "Процедура {} не возвращает значения", name)
+            } else {
+                write!(f, "Procedure {} does not return a value", name)
+            },
+            Message::ResultRequired(name) => if ru {
+                write!(f, "Функция {} должна вернуть значение", name)
+            } else {
+                write!(f, "Function {} must return a value", name)
+            },
+            Message::ArgumentCount(name, expected, found) => if ru {
+                write!(f, "{} ожидает аргументов: {}, передано: {}", name, expected, found)
+            } else {
+                write!(f, "{} takes {} argument(s) but {} were given", name, expected, found)
+            },
+            Message::DeclaredHere => f.write_str(tr("объявлена здесь", "declared here")),
+            Message::NotArray(name) => if ru {
+                write!(f, "Переменная {} не является массивом", name)
+            } else {
+                write!(f, "Variable {} is not an array", name)
+            },
+            Message::ArrayUsage(name) => if ru {
+                write!(f, "Массив {} используется без индекса", name)
+            } else {
+                write!(f, "Array {} is used without an index", name)
+            },
+            Message::IndexType(t) => if ru {
+                write!(f, "Индекс массива должен иметь тип integer, а не {}", t)
+            } else {
+                write!(f, "Array index must be integer, not {}", t)
+            },
+            Message::ConstantChange(name) => if ru {
+                write!(f, "Значение константы {} нельзя изменить", name)
+            } else {
+                write!(f, "Cannot change the value of constant {}", name)
+            },
+            Message::NotConstant => f.write_str(tr(
+                "Значение константы должно вычисляться при компиляции",
+                "Constant value must be computable at compile time"
+            )),
+            Message::NonConstant => f.write_str(tr("не константа", "not a constant")),
+            Message::CaseType(t) => if ru {
+                write!(f, "Выбор в case и метки должны иметь тип integer, а не {}", t)
+            } else {
+                write!(f, "Case selector and labels must be integer, not {}", t)
+            },
+            Message::DuplicateLabel(value) => if ru {
+                write!(f, "Метка {} повторяется в case", value)
+            } else {
+                write!(f, "Duplicate case label {}", value)
+            },
+            Message::FirstLabel => f.write_str(tr("первое использование", "first used here")),
+            Message::MaybeUninitialized(name) => if ru {
+                write!(f, "Переменная {} может быть не инициализирована", name)
+            } else {
+                write!(f, "Variable {} may be uninitialized", name)
+            },
+            Message::UnusedVariable(name) => if ru {
+                write!(f, "Переменная {} не используется", name)
+            } else {
+                write!(f, "Variable {} is never used", name)
+            },
+            Message::UnusedAssignment(name) => if ru {
+                write!(f, "Значение, присвоенное {}, не используется", name)
+            } else {
+                write!(f, "Value assigned to {} is never read", name)
+            },
+            Message::UnusedInput(name) => if ru {
+                write!(f, "Значение {} только вводится и не используется", name)
+            } else {
+                write!(f, "Variable {} is only read by `input` and never used", name)
+            },
+            Message::InputHere => f.write_str(tr("введено здесь", "read here")),
+            Message::ConstantCondition => f.write_str(tr("Условие всегда ложно", "Condition is always false")),
+            Message::InfiniteLoop => f.write_str(tr("Цикл никогда не завершается", "Loop never exits")),
+            Message::AlwaysTrue => f.write_str(tr(
+                "условие всегда истинно, а break и return нет",
+                "condition is always true and there is no `break` or `return`"
+            )),
+            Message::UnreachableCode => f.write_str(tr(
+                "Код после этого оператора не выполняется",
+                "Code after this statement is unreachable"
+            )),
+            Message::LintDefault(name) => if ru {
+                write!(f, "`-W {}` включено по умолчанию", name)
+            } else {
+                write!(f, "`-W {}` is on by default", name)
+            },
+            Message::LintDenied(name) => if ru {
+                write!(f, "`-D {}` следует из `--deny-warnings`", name)
+            } else {
+                write!(f, "`-D {}` is implied by `--deny-warnings`", name)
+            },
+            Message::UnassignedIf(name, condition) => match (ru, condition) {
+                (true, true) => write!(f, "{} не получает значения, если условие истинно", name),
+                (true, false) => write!(f, "{} не получает значения, если условие ложно", name),
+                (false, true) => write!(f, "{} is not assigned when this condition is true", name),
+                (false, false) => write!(f, "{} is not assigned when this condition is false", name)
+            },
+            Message::UnassignedLoop(name) => if ru {
+                write!(f, "{} не получает значения, если тело цикла не выполняется", name)
+            } else {
+                write!(f, "{} is not assigned if the loop body never runs", name)
+            },
+            Message::UnassignedBreak(name) => if ru {
+                write!(f, "{} не получает значения перед этим break", name)
+            } else {
+                write!(f, "{} is not assigned before this `break`", name)
+            },
+            Message::UnassignedArm(name) => if ru {
+                write!(f, "{} не получает значения в этой ветви", name)
+            } else {
+                write!(f, "{} is not assigned in this branch", name)
+            },
+            Message::UnassignedNoLabel(name) => if ru {
+                write!(f, "{} не получает значения, если ни одна метка не подошла", name)
+            } else {
+                write!(f, "{} is not assigned when no label matches", name)
+            },
+            Message::StorageSize => f.write_str(tr(
+                "Переменным нужно больше 2 ГиБ памяти",
+                "Variables need more than 2 GiB of memory"
+            )),
+            Message::OuterScope(name, line) => if ru {
+                write!(f, "{} перекрывает переменную, объявленную во внешней области видимости в строке {}", name, line)
+            } else {
+                write!(f, "{} shadows a variable declared in outer scope on line {}", name, line)
+            },
+            Message::OutsideLoop(keyword) => if ru {
+                write!(f, "Оператор {} используется вне цикла", keyword)
+            } else {
+                write!(f, "`{}` used outside of a loop", keyword)
+            },
+
+            Message::ObjectFailed(e) => if ru {
+                write!(f, "Не удалось создать объектный файл: {}", e)
+            } else {
+                write!(f, "Failed to create object file: {}", e)
+            },
+
+            Message::DivisionError => f.write_str(tr(
+                "Целочисленное деление на ноль или переполнение при делении",
+                "Integer division by zero or overflow"
+            )),
+            Message::Divisor => f.write_str(tr("делитель", "divisor")),
+            Message::ArrayAllocation(len) => if ru {
+                write!(f, "Не удалось выделить память под массив длины {}", len)
+            } else {
+                write!(f, "Cannot allocate memory for array of length {}", len)
+            },
+            Message::IndexOutOfBounds(index, len) => if ru {
+                write!(f, "Индекс {} вне границ массива длины {}", index, len)
+            } else {
+                write!(f, "Index {} is out of bounds for array of length {}", index, len)
+            },
+            Message::RuntimeIo(e) => if ru {
+                write!(f, "Ошибка ввода-вывода при выполнении: {}", e)
+            } else {
+                write!(f, "I/O error while running: {}", e)
+            }
+        }
+    }
+}
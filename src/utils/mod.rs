@@ -3,4 +3,9 @@ pub mod parser;
 pub mod syntax;
 pub mod structs;
 pub mod semantic;
-pub mod elf;
\ No newline at end of file
+pub mod elf;
+pub mod linker;
+pub mod interpreter;
+pub mod diagnostic;
+pub mod compiler;
+pub mod messages;
@@ -1,13 +1,13 @@
-use std::{collections::HashMap, fs};
+use std::{collections::{HashMap, HashSet}, fs, io::{self, BufRead, Write}};
 
-use crate::utils::{lexer::Lexer, structs::tokens::TokenGroup};
+use crate::utils::{lexer::Lexer, linker, messages::Message, structs::tokens::TokenGroup};
 
-use super::{structs::{program::MainOperation, tokens::Token, types::LexerDigitalData}, syntax::Syntax};
+use super::{diagnostic::{Diagnostic, Phase, Severity}, elf::Elf, interpreter::{error::RuntimeError, Interpreter}, semantic::{error::{Path, SemanticError}, lint::{Level, Lints, Warning}, Semantic}, structs::{program::MainOperation, tokens::Token, types::LexerDigitalData}, syntax::Syntax};
 
 #[derive(Debug, Clone)]
 pub struct Parser {
     path: String,
-    lexer: Lexer,
+    pub source: String,
     syntax: Syntax,
 
     pub tokens: Vec<Token>,
@@ -15,69 +15,99 @@ pub struct Parser {
     last_var: u64,
 
     pub program: Vec<MainOperation>,
+    partial: bool,
 
     pub ident_map: HashMap<String, u64>,
     pub var_map: HashMap<String, u64>,
 
     pub vars: HashMap<u64, LexerDigitalData>,
+    /// Строковые литералы, номер лексемы `Strings` - индекс в списке
+    pub strings: Vec<String>,
+
+    pub asm: Vec<u8>,
+    pub asm_idents: Vec<(u64, u64, bool)>,
+    arrays: Vec<(u64, u64)>,
+    constant_ids: HashSet<u64>,
+    bounds_checked: bool,
+    division_checked: bool,
+
+    /// Уровни предупреждений семантического анализа
+    pub lints: Lints,
+    /// Оптимизации `-O1`
+    pub optimize: bool,
+    /// Предупреждения успешного семантического анализа
+    pub warnings: Vec<Diagnostic>,
 }
 
 impl Parser {
-    pub fn new(path: impl Into<String>) -> Self {
+    pub fn new(path: impl Into<String>) -> io::Result<Self> {
         let path: String = path.into();
-        let file = fs::File::open(path.clone()).expect("Не удалось открыть файл");
-        let lexer = Lexer::new(file, path.clone());
+        let source = fs::read_to_string(&path)?;
+        Ok(Self::from_source(source, path))
+    }
+
+    pub fn from_source(source: impl Into<String>, path: impl Into<String>) -> Self {
+        let path: String = path.into();
+        let source: String = source.into();
         let syntax = Syntax::new();
         Self {
             path,
-            lexer,
+            source,
             syntax,
             tokens: Vec::new(),
             last_ident: 0,
             last_var: 0,
             program: Vec::default(),
+            partial: false,
             ident_map: HashMap::new(),
             var_map: HashMap::new(),
             vars: HashMap::new(),
+            strings: Vec::new(),
+            asm: Vec::new(),
+            asm_idents: Vec::new(),
+            arrays: Vec::new(),
+            constant_ids: HashSet::new(),
+            bounds_checked: false,
+            division_checked: false,
+            lints: Lints::default(),
+            optimize: false,
+            warnings: Vec::new(),
         }
     }
 
-    pub fn run_lexer(&mut self) -> Result<(), ()> {
+    pub fn run_lexer(&mut self) -> Result<(), Vec<Diagnostic>> {
         use crate::utils::structs::tokens::TokenGroupLexer;
         
-        let mut has_illegals = false;
+        let mut illegals = Vec::new();
 
-        loop {
-            let token = self.lexer.next_token();
+        for token in Lexer::new(&self.source, self.path.clone()) {
             match token {
-                Ok((token, line, column)) => {
+                Ok((token, span)) => {
                     match token.clone() {
-                        TokenGroupLexer::Illegal(i) => {
-                            println!("Обнаружен непредвиденный символ: {} в {}:{}:{}", i.0, self.path, i.1, i.2);
-                            has_illegals = true;
-                        },
+                        TokenGroupLexer::Illegal(i) => illegals.push(Diagnostic::new(
+                            Phase::Lexer,
+                            self.path.clone(),
+                            Some(span),
+                            Message::UnexpectedCharacter(i.0).to_string()
+                        ).code("E0001")),
                         TokenGroupLexer::Keywords(v) => self.tokens.push(Token {
                             token: TokenGroup::Keywords(v),
-                            line,
-                            column
+                            span
                         }),
                         TokenGroupLexer::Delimiters(v) => self.tokens.push(Token {
                             token: TokenGroup::Delimiters(v),
-                            line,
-                            column
+                            span
                         }),
                         TokenGroupLexer::Identifier(v) => match self.ident_map.get(&v) {
                             Some(v) => self.tokens.push(Token {
                                 token: TokenGroup::Identifier(*v),
-                                line,
-                                column
+                                span
                             }),
                             None => {
                                 self.ident_map.insert(v, self.last_ident);
                                 self.tokens.push(Token {
                                     token: TokenGroup::Identifier(self.last_ident),
-                                    line,
-                                    column
+                                    span
                                 });
                                 self.last_ident += 1;
                             }
@@ -85,113 +115,467 @@ impl Parser {
                         TokenGroupLexer::Variables(v) => match self.var_map.get(&v) {
                             Some(v) => self.tokens.push(Token {
                                 token: TokenGroup::Variables(*v),
-                                line,
-                                column
+                                span
                             }),
                             None => {
+                                match number(&v) {
+                                    Some(value) => {
+                                        self.vars.insert(self.last_var, value);
+                                    },
+                                    None => illegals.push(Diagnostic::new(
+                                        Phase::Lexer,
+                                        self.path.clone(),
+                                        Some(span),
+                                        Message::NumberOverflow(v.clone()).to_string()
+                                    ).code("E0003"))
+                                }
                                 self.var_map.insert(v, self.last_var);
                                 self.tokens.push(Token {
                                     token: TokenGroup::Variables(self.last_var),
-                                    line,
-                                    column
+                                    span
                                 });
                                 self.last_var += 1;
                             }
                         },
+                        TokenGroupLexer::String(v) => {
+                            // printf оборвал бы строку на нулевом символе
+                            if v.contains('\0') {
+                                illegals.push(Diagnostic::new(
+                                    Phase::Lexer,
+                                    self.path.clone(),
+                                    Some(span),
+                                    Message::UnexpectedCharacter('\0').to_string()
+                                ).code("E0001"));
+                            }
+                            let id = match self.strings.iter().position(|s| *s == v) {
+                                Some(id) => id,
+                                None => {
+                                    self.strings.push(v);
+                                    self.strings.len() - 1
+                                }
+                            };
+                            self.tokens.push(Token {
+                                token: TokenGroup::Strings(id as u64),
+                                span
+                            });
+                        },
                         TokenGroupLexer::Eof => {
                             self.tokens.push(Token {
                                 token: TokenGroup::Eof,
-                                line,
-                                column
+                                span
                             });
                             break;
                         },
                     };
                 },
-                Err(e) => return Err(println!("{e}")),
+                Err(e) => {
+                    illegals.push(Diagnostic::new(
+                        Phase::Lexer,
+                        self.path.clone(),
+                        Some(e.span),
+                        Message::LexerFailed(format!("{:?}", e.token), e.message).to_string()
+                    ).code("E0002"));
+                    return Err(illegals);
+                },
             }
         }
 
-        for var in self.var_map.clone() {
-            let last_char = var.0.chars().last().unwrap();
-            match last_char {
-                'B'|'b' => {
-                    let digit = &var.0[..var.0.len()-1];
-                    let digit = i64::from_str_radix(digit, 2).unwrap();
-                    self.vars.insert(var.1, LexerDigitalData::Integer(digit));
-                },
-                'O'|'o' => {
-                    let digit = &var.0[..var.0.len()-1];
-                    let digit = i64::from_str_radix(digit, 8).unwrap();
-                    self.vars.insert(var.1, LexerDigitalData::Integer(digit));
-                },
-                'H'|'h' => {
-                    let digit = &var.0[..var.0.len()-1];
-                    let digit = i64::from_str_radix(digit, 16).unwrap();
-                    self.vars.insert(var.1, LexerDigitalData::Integer(digit));
-                },
-                'D'|'d' => {
-                    let digit = &var.0[..var.0.len()-1];
-                    let digit = digit.parse().unwrap();
-                    self.vars.insert(var.1, LexerDigitalData::Integer(digit));
-                },
-                _ => {
-                    let digits = var.0;
-                    if digits.contains('.') || digits.contains('e') || digits.contains('E') {
-                        let digit = digits.parse().unwrap();
-                        self.vars.insert(var.1, LexerDigitalData::Float(digit));
-                    } else {
-                        let digit = digits.parse().unwrap();
-                        self.vars.insert(var.1, LexerDigitalData::Integer(digit));
-                    }
-                }
-            }
-        };
-        if has_illegals {
-            Err(())
-        } else {
+        if illegals.is_empty() {
             Ok(())
+        } else {
+            Err(illegals)
         }
     }
 
-    pub fn run_syntax(&mut self, compact_mode: bool) -> Result<(), ()> {
-        let res = self.syntax.run_process(
+    /// Синтаксический анализ с восстановлением после ошибок: даже при
+    /// ошибках в `program` остаётся частично разобранная программа
+    pub fn run_syntax(&mut self) -> Result<(), Vec<Diagnostic>> {
+        let (program, errors) = self.syntax.run_process(
             self.tokens.clone(),
             self.vars.clone()
         );
-        
+        self.program = program;
+        self.partial = !errors.is_empty();
+
         use crate::utils::syntax::error::SyntaxError;
-        match res {
-            Ok(data) => {
-                self.program = data;
-                if !compact_mode {
-                    println!("{:#?}", self.program);
-                }
-                Ok(())
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(errors.into_iter().map(|e| match e {
+            SyntaxError::Missing(token, text) => Diagnostic::new(
+                Phase::Syntax,
+                self.path.clone(),
+                Some(token.span),
+                Message::FoundToken(text.to_string(), format!("{:?}", token.token)).to_string()
+            ).code("E0101").label(token.span, text.to_string()),
+            SyntaxError::Error(span, text) => Diagnostic::new(
+                Phase::Syntax,
+                self.path.clone(),
+                Some(span),
+                text.to_string()
+            ).code("E0102")
+        }).collect())
+    }
+
+    pub fn run_semantic(&mut self) -> Result<(), Vec<Diagnostic>> {
+        let mut semantic = Semantic::new(self.program.clone(), self.vars.clone(), self.ident_map.len() as u64);
+        semantic.partial = self.partial;
+        semantic.optimize = self.optimize;
+        let res = semantic.run_process();
+        // запрещённые через `-D` предупреждения становятся ошибками
+        let (denied, mut warnings): (Vec<Diagnostic>, Vec<Diagnostic>) = std::mem::take(&mut semantic.warnings).into_iter()
+            .filter_map(|w| self.lint_diagnostic(w))
+            .partition(|d| d.severity == Severity::Error);
+        // тела функций проверяются после основного блока,
+        // а сообщения выводятся в порядке текста программы
+        warnings.sort_by_key(|d| d.span.map(|s| (s.line, s.column)));
+        self.warnings = warnings;
+        if res.is_err() || !denied.is_empty() {
+            let mut diagnostics: Vec<Diagnostic> = res.err().unwrap_or_default().into_iter()
+                .map(|e| self.semantic_diagnostic(e))
+                .chain(denied)
+                .collect();
+            diagnostics.sort_by_key(|d| d.span.map(|s| (s.line, s.column)));
+            return Err(diagnostics);
+        }
+        self.asm = semantic.asm;
+        self.asm_idents = semantic.asm_idents;
+        self.arrays = semantic.arrays;
+        self.constant_ids = semantic.constant_ids;
+        self.bounds_checked = semantic.bounds_checked;
+        self.division_checked = semantic.division_checked;
+        Ok(())
+    }
+
+    fn semantic_diagnostic(&self, e: SemanticError) -> Diagnostic {
+        match e {
+            SemanticError::AssignError(from, to, span, ident) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::AssignError(from.to_string(), to.to_string()).to_string()
+            ).code("E0204").label(span, from.to_string()).secondary(ident, to.to_string()),
+            SemanticError::InvalidOperation(t, o, span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::InvalidOperation(o, t.to_string()).to_string()
+            ).code("E0205").label(span, t.to_string()),
+            SemanticError::NotDefined(id, span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::NotDefined(self.ident_name(id).into()).to_string()
+            ).code("E0201"),
+            SemanticError::TypeError(s, f, s_span, f_span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(s_span),
+                Message::TypeError(f.to_string(), s.to_string()).to_string()
+            ).code("E0203").label(s_span, s.to_string()).secondary(f_span, f.to_string()),
+            SemanticError::NotBoolean(t, span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::NotBoolean(t.to_string()).to_string()
+            ).code("E0206").label(span, t.to_string()),
+            SemanticError::OutsideLoop(keyword, span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::OutsideLoop(keyword.into()).to_string()
+            ).code("E0207"),
+            SemanticError::OutsideFunction(span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::OutsideFunction.to_string()
+            ).code("E0208"),
+            SemanticError::ReturnValue(id, expected, span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                if expected {
+                    Message::ResultRequired(self.ident_name(id).into())
+                } else {
+                    Message::NoResult(self.ident_name(id).into())
+                }.to_string()
+            ).code("E0209"),
+            SemanticError::ArgumentCount(id, expected, found, span, declaration) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::ArgumentCount(self.ident_name(id).into(), expected, found).to_string()
+            ).code("E0210").label(span, found.to_string()).secondary(declaration, Message::DeclaredHere.to_string()),
+            SemanticError::NoResult(id, span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::NoResult(self.ident_name(id).into()).to_string()
+            ).code("E0211"),
+            SemanticError::NotArray(id, span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::NotArray(self.ident_name(id).into()).to_string()
+            ).code("E0212"),
+            SemanticError::ArrayUsage(id, span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::ArrayUsage(self.ident_name(id).into()).to_string()
+            ).code("E0213"),
+            SemanticError::IndexType(t, span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::IndexType(t.to_string()).to_string()
+            ).code("E0214").label(span, t.to_string()),
+            SemanticError::IdentifierAlreadyDeclared(id, span, first) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::AlreadyDeclared(self.ident_name(id).into()).to_string()
+            ).code("E0202").label(span, Message::Redeclaration.to_string()).secondary(first, Message::FirstDeclaration.to_string()),
+            SemanticError::ConstantChange(id, span, declaration) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::ConstantChange(self.ident_name(id).into()).to_string()
+            ).code("E0215").secondary(declaration, Message::DeclaredHere.to_string()),
+            SemanticError::NotConstant(span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::NotConstant.to_string()
+            ).code("E0216").label(span, Message::NonConstant.to_string()),
+            SemanticError::ConstantDivision(span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::DivisionError.to_string()
+            ).code("E0217").label(span, Message::Divisor.to_string()),
+            SemanticError::CaseType(t, span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::CaseType(t.to_string()).to_string()
+            ).code("E0218").label(span, t.to_string()),
+            SemanticError::DuplicateLabel(value, span, first) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::DuplicateLabel(value).to_string()
+            ).code("E0219").secondary(first, Message::FirstLabel.to_string()),
+            SemanticError::MaybeUninitialized(id, span, path) => {
+                let name: String = self.ident_name(id).into();
+                let (path_span, message) = match path {
+                    Path::Then(span) => (span, Message::UnassignedIf(name.clone(), true)),
+                    Path::Else(span) => (span, Message::UnassignedIf(name.clone(), false)),
+                    Path::Loop(span) => (span, Message::UnassignedLoop(name.clone())),
+                    Path::Break(span) => (span, Message::UnassignedBreak(name.clone())),
+                    Path::Arm(span) => (span, Message::UnassignedArm(name.clone())),
+                    Path::NoLabel(span) => (span, Message::UnassignedNoLabel(name.clone()))
+                };
+                Diagnostic::new(
+                    Phase::Semantic,
+                    self.path.clone(),
+                    Some(span),
+                    Message::MaybeUninitialized(name).to_string()
+                ).code("E0220").secondary(path_span, message.to_string())
             },
-            Err(e) => match e {
-                SyntaxError::Missing(token, text) => {
-                    let (line, column) = match self.tokens.get(self.syntax.position-1) {
-                        Some(Token { line, column, .. }) => (*line, *column),
-                        None => (0, 0)
-                    };
+            SemanticError::StorageSize(span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::StorageSize.to_string()
+            ).code("E0221"),
+            SemanticError::Shadowing(e, id, outer) => self.semantic_diagnostic(*e).note(
+                Message::OuterScope(self.ident_name(id).into(), outer.line).to_string()
+            )
+        }
+    }
 
-                    return Err(println!(
-                        "Синтаксический анализатор сообщает: {}, а встречена лексема: {:?} по адресу {}:{}:{}",
-                        text,
-                        token.token,
-                        self.path,
-                        line,
-                        column
-                    ))
-                },
-                SyntaxError::Error(text) => 
-                    return Err(println!(
-                        "Синтаксический анализатор сообщает: {}",
-                        text
-                    ))
+    /// Предупреждение с уровнем из `lints`, отключённые пропускаются
+    fn lint_diagnostic(&self, w: Warning) -> Option<Diagnostic> {
+        let lint = w.lint();
+        let severity = match self.lints.level(lint) {
+            Level::Allow => return None,
+            Level::Warn => Severity::Warning,
+            Level::Deny => Severity::Error
+        };
+        let diagnostic = match w {
+            Warning::UnusedVariable(id, span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::UnusedVariable(self.ident_name(id).into()).to_string()
+            ),
+            Warning::UnusedAssignment(id, span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::UnusedAssignment(self.ident_name(id).into()).to_string()
+            ),
+            Warning::UnusedInput(id, span, at) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::UnusedInput(self.ident_name(id).into()).to_string()
+            ).secondary(at, Message::InputHere.to_string()),
+            Warning::ConstantCondition(span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::ConstantCondition.to_string()
+            ),
+            Warning::InfiniteLoop(span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::InfiniteLoop.to_string()
+            ).label(span, Message::AlwaysTrue.to_string()),
+            Warning::UnreachableCode(span) => Diagnostic::new(
+                Phase::Semantic,
+                self.path.clone(),
+                Some(span),
+                Message::UnreachableCode.to_string()
+            )
+        }.code(lint.code()).severity(severity);
+        Some(match (self.lints.explicit(lint), severity) {
+            (true, _) => diagnostic,
+            (false, Severity::Error) => diagnostic.note(Message::LintDenied(lint.name()).to_string()),
+            (false, _) => diagnostic.note(Message::LintDefault(lint.name()).to_string())
+        })
+    }
+
+    pub fn run_elf(&self) -> Result<Vec<u8>, Vec<Diagnostic>> {
+        // строки формата для dprintf: номер строки, индекс и длина массива
+        // или только номер строки, коды те же, что у интерпретатора
+        let path = self.path.replace('%', "%%");
+        let message = self.bounds_checked.then(|| format!(
+            "{}:%ld: {}[E0503]: {}\n",
+            path,
+            Message::Error,
+            Message::IndexOutOfBounds("%ld".into(), "%ld".into())
+        ));
+        let division = self.division_checked.then(|| format!(
+            "{}:%ld: {}[E0501]: {}\n",
+            path,
+            Message::Error,
+            Message::DivisionError.to_string().replace('%', "%%")
+        ));
+        let mut elf = Elf::new(
+            self.ident_map.len() as u16,
+            self.asm.clone(),
+            self.asm_idents.clone(),
+            self.arrays.clone(),
+            &self.constant_ids,
+            (message, division),
+            self.strings.clone()
+        );
+        elf.process().map_err(|e| vec![Diagnostic::new(
+            Phase::Object,
+            self.path.clone(),
+            None,
+            Message::ObjectFailed(e.to_string()).to_string()
+        ).code("E0301")])
+    }
+
+    /// Компонует объектный файл в исполняемый `output`
+    pub fn run_link(&self, object: &[u8], output: &str) -> Result<(), Vec<Diagnostic>> {
+        linker::link(object, output).map_err(|e| vec![Diagnostic::new(
+            Phase::Link,
+            self.path.clone(),
+            None,
+            Message::LinkFailed(e).to_string()
+        ).code("E0401")])
+    }
+
+    /// Выполняет проверенную программу интерпретатором
+    pub fn run_interpreter(&self, input: impl BufRead, output: impl Write) -> Result<(), Vec<Diagnostic>> {
+        let mut interpreter = Interpreter::new(self.program.clone(), self.vars.clone(), self.strings.clone(), input, output);
+        interpreter.run_process().map_err(|e| vec![match e {
+            RuntimeError::DivisionError(span) => Diagnostic::new(
+                Phase::Runtime,
+                self.path.clone(),
+                Some(span),
+                Message::DivisionError.to_string()
+            ).code("E0501").label(span, Message::Divisor.to_string()),
+            RuntimeError::Io(e) => Diagnostic::new(
+                Phase::Runtime,
+                self.path.clone(),
+                None,
+                Message::RuntimeIo(e).to_string()
+            ).code("E0502"),
+            RuntimeError::IndexOutOfBounds(span, index, len) => Diagnostic::new(
+                Phase::Runtime,
+                self.path.clone(),
+                Some(span),
+                Message::IndexOutOfBounds(index.to_string(), len.to_string()).to_string()
+            ).code("E0503"),
+            RuntimeError::ArrayAllocation(span, len) => Diagnostic::new(
+                Phase::Runtime,
+                self.path.clone(),
+                Some(span),
+                Message::ArrayAllocation(len.to_string()).to_string()
+            ).code("E0504")
+        }])
+    }
+
+    /// Лексемы и таблица идентификаторов и чисел в том виде,
+    /// в котором их печатает команда `lex`
+    pub fn token_table(&self, lex_objects: bool) -> String {
+        let mut res = String::new();
+        for i in &self.tokens {
+            if lex_objects {
+                res += &format!("{:?}\n", i.token);
+            } else {
+                res += &format!("{} ", i.token);
             }
         }
+        res += "\n";
+        let mut identifiers = self.ident_map.clone().into_iter()
+            .map(|(name, id)| (id, name)).collect::<Vec<(u64, String)>>();
+        identifiers.sort_by_key(|a| a.0);
+        let mut variables = self.vars.clone()
+            .into_iter().collect::<Vec<(u64, LexerDigitalData)>>();
+        variables.sort_by_key(|a| a.0);
+        res += &format!("{}\n", Message::TableHeader);
+        res += "|---|----------------------|----|---------------------|\n";
+        for i in 0..identifiers.len().max(variables.len()) {
+            res += &format!("|{} | {}|\n", match identifiers.get(i) {
+                Some((id, name)) => format!("{0: <2} | {1: <20}", id, name),
+                None => format!("{0: <2} | {1: <20}", "", "")
+            }, match variables.get(i) {
+                Some((id, value)) => format!("{0: <2} | {1: <20}", id, value.to_string()),
+                None => format!("{0: <2} | {1: <20}", "", "")
+            });
+        }
+        res
     }
 
-}
\ No newline at end of file
+    pub fn ident_name(&self, id: u64) -> &str {
+        match self.ident_map.iter().find(|(_, v)| **v == id) {
+            Some((name, _)) => name,
+            None => ""
+        }
+    }
+
+}
+
+/// Значение числового литерала по суффиксу системы счисления,
+/// `None` - целое не помещается в 64 бита
+fn number(literal: &str) -> Option<LexerDigitalData> {
+    let digits = &literal[..literal.len()-1];
+    let integer = match literal.chars().last()? {
+        'B'|'b' => i64::from_str_radix(digits, 2),
+        'O'|'o' => i64::from_str_radix(digits, 8),
+        'H'|'h' => i64::from_str_radix(digits, 16),
+        'D'|'d' => digits.parse(),
+        _ if literal.contains('.') || literal.contains('e') || literal.contains('E') =>
+            return literal.parse().ok().map(LexerDigitalData::Float),
+        _ => literal.parse()
+    };
+    integer.ok().map(LexerDigitalData::Integer)
+}
@@ -0,0 +1,185 @@
+use crate::utils::{interpreter::{power, Value}, messages::Message, structs::{program::{Expression, Multiplier, Operand, Term}, types::{AdditionOperations, LexerDigitalData, MultiplicationOperations, ProgramTypes, RelationOperations}}};
+
+use super::{error::{SemanticError, SemanticResult}, Semantic};
+
+/// Вычисление значений констант при компиляции. Типы проверяются по тем же
+/// правилам, что и при генерации кода, а значения совпадают с машинным кодом.
+/// Значение хранится в типе: `Integer(Some(v))`, `Float(Some(v))`, `Boolean(Some(v))`
+impl Semantic {
+    pub(super) fn evaluate(&self, expression: &Expression) -> SemanticResult<ProgramTypes> {
+        let mut operands = expression.operands.iter();
+        let first = operands.next().unwrap();
+        let mut left_span = first.span;
+        let mut left = self.evaluate_operand(first)?;
+
+        for (operand, operation) in operands.zip(&expression.operations) {
+            let span = operand.span;
+            let right = self.evaluate_operand(operand)?;
+            if let ProgramTypes::Boolean(_) = right {
+                if !matches!(operation, RelationOperations::Equal|RelationOperations::NotEqual) {
+                    return Err(SemanticError::InvalidOperation(right, operation.clone().into(), span));
+                }
+            }
+            let res = match widen(&left, &right) {
+                Some((ProgramTypes::Integer(Some(a)), ProgramTypes::Integer(Some(b)))) => compare(a, b, operation),
+                Some((ProgramTypes::Float(Some(a)), ProgramTypes::Float(Some(b)))) => compare(a, b, operation),
+                Some((ProgramTypes::Boolean(Some(a)), ProgramTypes::Boolean(Some(b)))) => compare(a, b, operation),
+                _ => return Err(SemanticError::TypeError(right, left, span, left_span))
+            };
+            left_span = left_span.to(span);
+            left = ProgramTypes::Boolean(Some(res));
+        }
+
+        Ok(left)
+    }
+
+    pub(super) fn evaluate_operand(&self, operand: &Operand) -> SemanticResult<ProgramTypes> {
+        let mut terms = operand.terms.iter();
+        let first = terms.next().unwrap();
+        let mut left_span = first.span;
+        let mut left = self.evaluate_term(first)?;
+
+        for (term, operation) in terms.zip(&operand.operations) {
+            let span = term.span;
+            let right = self.evaluate_term(term)?;
+            let boolean = matches!(right, ProgramTypes::Boolean(_));
+            if boolean != matches!(operation, AdditionOperations::Or) {
+                return Err(SemanticError::InvalidOperation(right, operation.to_string(), span));
+            }
+            left = match (widen(&left, &right), operation) {
+                (Some((ProgramTypes::Integer(Some(a)), ProgramTypes::Integer(Some(b)))), AdditionOperations::Addition) =>
+                    ProgramTypes::Integer(Some(a.wrapping_add(b))),
+                (Some((ProgramTypes::Integer(Some(a)), ProgramTypes::Integer(Some(b)))), _) =>
+                    ProgramTypes::Integer(Some(a.wrapping_sub(b))),
+                (Some((ProgramTypes::Float(Some(a)), ProgramTypes::Float(Some(b)))), AdditionOperations::Addition) =>
+                    ProgramTypes::Float(Some(a + b)),
+                (Some((ProgramTypes::Float(Some(a)), ProgramTypes::Float(Some(b)))), _) =>
+                    ProgramTypes::Float(Some(a - b)),
+                (Some((ProgramTypes::Boolean(Some(a)), ProgramTypes::Boolean(Some(b)))), _) =>
+                    ProgramTypes::Boolean(Some(a || b)),
+                _ => return Err(SemanticError::TypeError(right, left, span, left_span))
+            };
+            left_span = left_span.to(span);
+        }
+
+        Ok(left)
+    }
+
+    pub(super) fn evaluate_term(&self, term: &Term) -> SemanticResult<ProgramTypes> {
+        let mut multipliers = term.multipliers.iter();
+        let first = multipliers.next().unwrap();
+        let mut left_span = first.span();
+        let mut left = self.evaluate_multiplier(first)?;
+
+        for (multiplier, operation) in multipliers.zip(&term.operations) {
+            let span = multiplier.span();
+            let right = self.evaluate_multiplier(multiplier)?;
+            let boolean = matches!(right, ProgramTypes::Boolean(_));
+            if boolean != matches!(operation, MultiplicationOperations::And) {
+                return Err(SemanticError::InvalidOperation(right, operation.to_string(), span));
+            }
+            if let MultiplicationOperations::Remainder|MultiplicationOperations::IntegerDivision = operation {
+                for (t, span) in [(&left, left_span), (&right, span)] {
+                    if !matches!(t, ProgramTypes::Integer(_)) {
+                        return Err(SemanticError::InvalidOperation(t.clone(), operation.to_string(), span));
+                    }
+                }
+            }
+            left = match (widen(&left, &right), operation) {
+                (Some((ProgramTypes::Integer(Some(a)), ProgramTypes::Integer(Some(b)))), MultiplicationOperations::Multiplication) =>
+                    ProgramTypes::Integer(Some(a.wrapping_mul(b))),
+                (Some((ProgramTypes::Integer(Some(a)), ProgramTypes::Integer(Some(b)))), MultiplicationOperations::Remainder) =>
+                    ProgramTypes::Integer(Some(a.checked_rem(b).ok_or(SemanticError::ConstantDivision(span))?)),
+                (Some((ProgramTypes::Integer(Some(a)), ProgramTypes::Integer(Some(b)))), _) =>
+                    ProgramTypes::Integer(Some(a.checked_div(b).ok_or(SemanticError::ConstantDivision(span))?)),
+                (Some((ProgramTypes::Float(Some(a)), ProgramTypes::Float(Some(b)))), MultiplicationOperations::Multiplication) =>
+                    ProgramTypes::Float(Some(a * b)),
+                (Some((ProgramTypes::Float(Some(a)), ProgramTypes::Float(Some(b)))), _) =>
+                    ProgramTypes::Float(Some(a / b)),
+                (Some((ProgramTypes::Boolean(Some(a)), ProgramTypes::Boolean(Some(b)))), _) =>
+                    ProgramTypes::Boolean(Some(a && b)),
+                _ => return Err(SemanticError::TypeError(right, left, span, left_span))
+            };
+            left_span = left_span.to(span);
+        }
+
+        Ok(left)
+    }
+
+    pub(super) fn evaluate_multiplier(&self, multiplier: &Multiplier) -> SemanticResult<ProgramTypes> {
+        match multiplier {
+            Multiplier::Identifier(ident) => match self.constants.get(&ident.id) {
+                Some(value) => Ok(value.clone()),
+                None if self.identifiers.contains_key(&ident.id) => Err(SemanticError::NotConstant(ident.span)),
+                None => Err(SemanticError::NotDefined(ident.id, ident.span))
+            },
+            Multiplier::Variable(id, _) => Ok(match self.vars[id] {
+                LexerDigitalData::Integer(v) => ProgramTypes::Integer(Some(v)),
+                LexerDigitalData::Float(v) => ProgramTypes::Float(Some(v))
+            }),
+            Multiplier::Boolean(b, _) => Ok(ProgramTypes::Boolean(Some(*b))),
+            Multiplier::Constant(value, _) => Ok(value.clone()),
+            Multiplier::Expression(e) => self.evaluate(e),
+            Multiplier::Not(m, span) => match self.evaluate_multiplier(m)? {
+                ProgramTypes::Boolean(Some(v)) => Ok(ProgramTypes::Boolean(Some(!v))),
+                t => Err(SemanticError::InvalidOperation(t, Message::Unary.to_string(), *span))
+            },
+            Multiplier::Neg(m, span) => match self.evaluate_multiplier(m)? {
+                ProgramTypes::Integer(Some(v)) => Ok(ProgramTypes::Integer(Some(v.wrapping_neg()))),
+                ProgramTypes::Float(Some(v)) => Ok(ProgramTypes::Float(Some(-v))),
+                t => Err(SemanticError::InvalidOperation(t, Message::UnaryMinus.to_string(), *span))
+            },
+            Multiplier::Convert(target, e, span) => match (self.evaluate(e)?, target) {
+                (ProgramTypes::Integer(Some(v)), ProgramTypes::Float(_)) => Ok(ProgramTypes::Float(Some(v as f64))),
+                (ProgramTypes::Float(Some(v)), ProgramTypes::Integer(_)) => match Value::Float(v).to_integer() {
+                    Value::Integer(v) => Ok(ProgramTypes::Integer(Some(v))),
+                    _ => unreachable!()
+                },
+                (ProgramTypes::Boolean(_), target) =>
+                    Err(SemanticError::InvalidOperation(ProgramTypes::Boolean(None), Message::Conversion(target.to_string()).to_string(), *span)),
+                (value, _) => Ok(value)
+            },
+            Multiplier::Power(base, exponent, span) => {
+                let (base_span, exponent_span) = (base.span(), exponent.span());
+                let (base, exponent) = (self.evaluate_multiplier(base)?, self.evaluate_multiplier(exponent)?);
+                for (t, span) in [(&base, base_span), (&exponent, exponent_span)] {
+                    if let ProgramTypes::Boolean(_) = t {
+                        return Err(SemanticError::InvalidOperation(t.clone(), Message::Power.to_string(), span));
+                    }
+                }
+                match widen(&base, &exponent) {
+                    Some((ProgramTypes::Float(Some(a)), ProgramTypes::Float(Some(b)))) => Ok(ProgramTypes::Float(Some(a.powf(b)))),
+                    Some((ProgramTypes::Integer(Some(a)), ProgramTypes::Integer(Some(b)))) =>
+                        Ok(ProgramTypes::Integer(Some(power(a, b).ok_or(SemanticError::ConstantDivision(*span))?))),
+                    _ => unreachable!()
+                }
+            },
+            Multiplier::Call(_, _, span)|
+            Multiplier::Index(_, _, span) => Err(SemanticError::NotConstant(*span))
+        }
+    }
+}
+
+/// Целое значение в паре с вещественным становится вещественным,
+/// значения разных типов не сочетаются
+fn widen(left: &ProgramTypes, right: &ProgramTypes) -> Option<(ProgramTypes, ProgramTypes)> {
+    match (left, right) {
+        (ProgramTypes::Integer(Some(a)), ProgramTypes::Float(b)) => Some((ProgramTypes::Float(Some(*a as f64)), ProgramTypes::Float(*b))),
+        (ProgramTypes::Float(a), ProgramTypes::Integer(Some(b))) => Some((ProgramTypes::Float(*a), ProgramTypes::Float(Some(*b as f64)))),
+        (left, right) if left.clone() & right.clone() => Some((left.clone(), right.clone())),
+        _ => None
+    }
+}
+
+/// Сравнение по IEEE 754, как в коде после `ucomisd`/`fucomi`:
+/// с NaN истинно только `!=`
+fn compare<T: PartialOrd>(a: T, b: T, operation: &RelationOperations) -> bool {
+    match operation {
+        RelationOperations::Equal => a == b,
+        RelationOperations::NotEqual => a != b,
+        RelationOperations::Greater => a > b,
+        RelationOperations::GreaterEqual => a >= b,
+        RelationOperations::Less => a < b,
+        RelationOperations::LessEqual => a <= b
+    }
+}
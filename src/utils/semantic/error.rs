@@ -1,13 +1,61 @@
-use crate::utils::structs::types::ProgramTypes;
+use crate::utils::structs::{span::Span, types::ProgramTypes};
 
 pub type SemanticResult<T> = Result<T, SemanticError>;
 
+/// Путь выполнения, на котором переменная не получает значения
+#[derive(Debug, Clone, Copy)]
+pub enum Path {
+    /// Условие `if` истинно
+    Then(Span),
+    /// Условие `if` ложно
+    Else(Span),
+    /// Тело цикла не выполняется ни разу
+    Loop(Span),
+    /// Выход из цикла через `break`
+    Break(Span),
+    /// Ветвь `case` с этими метками
+    Arm(Span),
+    /// Ни одна метка `case` не подошла
+    NoLabel(Span)
+}
+
 #[derive(Debug, Clone)]
 pub enum SemanticError {
-    NotDefined(u64),
-    IdentifierAlreadyDeclared(u64),
-    TypeError(ProgramTypes, ProgramTypes),
-    AssignError(ProgramTypes, ProgramTypes),
-    InvalidOperation(ProgramTypes, String),
-    NotBoolean(ProgramTypes)
-}
\ No newline at end of file
+    NotDefined(u64, Span),
+    IdentifierAlreadyDeclared(u64, Span, Span),
+    TypeError(ProgramTypes, ProgramTypes, Span, Span),
+    AssignError(ProgramTypes, ProgramTypes, Span, Span),
+    InvalidOperation(ProgramTypes, String, Span),
+    NotBoolean(ProgramTypes, Span),
+    /// `break` или `continue` вне тела цикла
+    OutsideLoop(&'static str, Span),
+    OutsideFunction(Span),
+    /// `return` без значения в функции или со значением в процедуре,
+    /// флаг показывает, ожидается ли значение
+    ReturnValue(u64, bool, Span),
+    ArgumentCount(u64, usize, usize, Span, Span),
+    /// Процедура использована в выражении
+    NoResult(u64, Span),
+    /// Индексируется переменная, которая не является массивом
+    NotArray(u64, Span),
+    /// Массив используется целиком там, где нужен элемент
+    ArrayUsage(u64, Span),
+    IndexType(ProgramTypes, Span),
+    /// Присваивание или ввод значения константы, место её объявления
+    ConstantChange(u64, Span, Span),
+    /// Значение константы нельзя вычислить при компиляции
+    NotConstant(Span),
+    /// Деление на ноль при вычислении константы
+    ConstantDivision(Span),
+    /// Выбор или метка `case` не целого типа
+    CaseType(ProgramTypes, Span),
+    /// Повторная метка `case` и её первое использование
+    DuplicateLabel(i64, Span, Span),
+    /// Переменная получает значение не на всех путях к месту использования
+    MaybeUninitialized(u64, Span, Path),
+    /// Переменная не помещается в кадр стека или в `.bss`
+    StorageSize(Span),
+    /// Ошибка с именем, которое перекрывает объявленное во внешней
+    /// области видимости, и место внешнего объявления
+    Shadowing(Box<SemanticError>, u64, Span)
+}
@@ -0,0 +1,333 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::utils::structs::{program::{Expression, MainOperation, Multiplier, Operator, OutputItem, Place}, span::Span, types::ProgramTypes};
+
+use super::{error::Path, Semantic};
+
+/// Состояние анализа присваиваний в точке программы: переменные без
+/// значения и путь, на котором присваивание пропускается (`None`, если
+/// значение не присваивается ни на одном пути), и присваивания, значение
+/// которых ещё может быть прочитано. После `break`, `continue` и `return`
+/// точка недостижима и в слиянии путей не участвует
+#[derive(Debug, Clone)]
+pub(super) struct State {
+    unassigned: HashMap<u64, Option<Path>>,
+    stores: HashMap<Span, Vec<Span>>,
+    reachable: bool
+}
+
+impl State {
+    pub(super) fn unreachable() -> Self {
+        Self { unassigned: HashMap::new(), stores: HashMap::new(), reachable: false }
+    }
+}
+
+impl Semantic {
+    pub(super) fn state(&self) -> State {
+        let unassigned = self.identifiers.iter()
+            .filter(|(_, t)| !assigned(t))
+            .map(|(id, _)| (*id, self.paths.get(id).copied()))
+            .collect();
+        State { unassigned, stores: self.stores.clone(), reachable: self.reachable }
+    }
+
+    pub(super) fn set_state(&mut self, state: State) {
+        let ids: Vec<u64> = self.identifiers.keys().copied().collect();
+        for id in ids {
+            let t = self.identifiers[&id].clone();
+            if let ProgramTypes::Array(..) = t {
+                continue;
+            }
+            if state.unassigned.contains_key(&id) {
+                self.identifiers.insert(id, without_value(&t));
+            } else {
+                self.initialize(id, &t);
+            }
+        }
+        self.paths = state.unassigned.into_iter()
+            .filter_map(|(id, path)| path.map(|path| (id, path)))
+            .collect();
+        self.stores = state.stores;
+        self.reachable = state.reachable;
+    }
+
+    /// Слияние путей выполнения. Переменная остаётся без значения, если она
+    /// не получила его хотя бы на одном достижимом пути, и запоминается
+    /// путь, на котором присваивание пропущено. Прочитано может быть
+    /// присваивание с любого из путей
+    pub(super) fn merge(branches: Vec<(State, Path)>) -> State {
+        let branches: Vec<_> = branches.into_iter().filter(|(state, _)| state.reachable).collect();
+        if branches.is_empty() {
+            return State::unreachable();
+        }
+        let mut unassigned = HashMap::new();
+        for (state, _) in &branches {
+            for id in state.unassigned.keys() {
+                if unassigned.contains_key(id) {
+                    continue;
+                }
+                let skips: Vec<_> = branches.iter()
+                    .filter_map(|(state, path)| state.unassigned.get(id).map(|known| (*known, *path)))
+                    .collect();
+                // путь из вложенного оператора точнее, иначе - первая ветвь без присваивания
+                let path = skips.iter().find_map(|(known, _)| *known)
+                    .or_else(|| (skips.len() < branches.len()).then(|| skips[0].1));
+                unassigned.insert(*id, path);
+            }
+        }
+        let mut stores: HashMap<Span, Vec<Span>> = HashMap::new();
+        for (state, _) in &branches {
+            for (declaration, spans) in &state.stores {
+                let merged = stores.entry(*declaration).or_default();
+                for span in spans {
+                    if !merged.contains(span) {
+                        merged.push(*span);
+                    }
+                }
+            }
+        }
+        State { unassigned, stores, reachable: true }
+    }
+
+    /// Присваивание переменной или ввод (`at` - `None`) заменяет значения,
+    /// которые до этого могли быть прочитаны
+    pub(super) fn store(&mut self, id: u64, at: Option<Span>) {
+        let Some(declaration) = self.variables.get(&id).copied() else {
+            return;
+        };
+        let stores = self.stores.entry(declaration).or_default();
+        stores.clear();
+        if let Some(at) = at {
+            stores.push(at);
+            self.assignments.push((id, at));
+        }
+    }
+
+    /// Присваивание элементу массива не затирает значения других элементов
+    pub(super) fn store_element(&mut self, id: u64, at: Span) {
+        if let Some(declaration) = self.variables.get(&id).copied() {
+            self.stores.entry(declaration).or_default().push(at);
+            self.assignments.push((id, at));
+        }
+    }
+
+    /// Чтение переменной использует все присваивания, которые до него доходят
+    pub(super) fn read(&mut self, id: u64) {
+        self.mark(id, |usage| usage.read = true);
+        if let Some(declaration) = self.variables.get(&id).copied() {
+            self.load(declaration);
+        }
+    }
+
+    fn load(&mut self, declaration: Span) {
+        self.loads.push(declaration);
+        if let Some(stores) = self.stores.remove(&declaration) {
+            self.used.extend(stores);
+        }
+    }
+
+    /// Вызванная подпрограмма читает глобальные переменные, которые
+    /// встречаются в ней или в вызванных из неё, а после возврата
+    /// из функции (`None`) прочитана может быть любая
+    pub(super) fn escape(&mut self, callee: Option<u64>) {
+        let globals: Vec<Span> = self.globals.iter()
+            .filter(|(id, _)| match callee {
+                Some(callee) => self.reads.get(&callee).is_some_and(|names| names.contains(id)),
+                None => true
+            })
+            .map(|(_, declaration)| *declaration)
+            .collect();
+        for declaration in globals {
+            self.load(declaration);
+        }
+    }
+
+    /// Конец итерации: присваивания доходят до следующей и используются,
+    /// если переменная читается в цикле после отметки `loads`
+    pub(super) fn backedge(&mut self, state: &State, loads: usize) {
+        if !state.reachable {
+            return;
+        }
+        let read: HashSet<Span> = self.loads[loads..].iter().copied().collect();
+        for (declaration, stores) in &state.stores {
+            if read.contains(declaration) {
+                self.used.extend(stores.iter().copied());
+            }
+        }
+    }
+
+    /// Присваивания, значение которых нигде не читается
+    pub(super) fn dead_stores(&self) -> Vec<(u64, Span)> {
+        self.assignments.iter()
+            .filter(|(_, at)| !self.used.contains(at))
+            .copied()
+            .collect()
+    }
+
+    /// Значение условия, если оно вычисляется при компиляции
+    pub(super) fn condition_value(&self, expression: &Expression) -> Option<bool> {
+        match self.evaluate(expression) {
+            Ok(ProgramTypes::Boolean(Some(value))) => Some(value),
+            _ => None
+        }
+    }
+}
+
+fn assigned(t: &ProgramTypes) -> bool {
+    !matches!(t, ProgramTypes::Boolean(None)|ProgramTypes::Float(None)|ProgramTypes::Integer(None))
+}
+
+fn without_value(t: &ProgramTypes) -> ProgramTypes {
+    match t {
+        ProgramTypes::Boolean(_) => ProgramTypes::Boolean(None),
+        ProgramTypes::Float(_) => ProgramTypes::Float(None),
+        ProgramTypes::Integer(_) => ProgramTypes::Integer(None),
+        t => t.clone()
+    }
+}
+
+/// Имена, которые встречаются в каждой подпрограмме и в вызванных из неё.
+/// Переменная может оказаться локальной, поэтому оценка сверху
+pub(super) fn reads(program: &[MainOperation]) -> HashMap<u64, HashSet<u64>> {
+    let mut reads: HashMap<u64, HashSet<u64>> = program.iter()
+        .filter_map(|main_operation| match main_operation {
+            MainOperation::Function(function) => {
+                let mut names = HashSet::new();
+                block_names(&function.body, &mut names);
+                Some((function.ident.id, names))
+            },
+            _ => None
+        })
+        .collect();
+    let functions: Vec<u64> = reads.keys().copied().collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for id in &functions {
+            let called: HashSet<u64> = reads[id].iter()
+                .filter(|name| *name != id)
+                .filter_map(|name| reads.get(name))
+                .flatten()
+                .copied()
+                .collect();
+            let names = reads.get_mut(id).unwrap();
+            let count = names.len();
+            names.extend(called);
+            changed |= names.len() != count;
+        }
+    }
+    reads
+}
+
+fn block_names(block: &[MainOperation], names: &mut HashSet<u64>) {
+    for main_operation in block {
+        match main_operation {
+            MainOperation::CreateConstant(_, expression) => expression_names(expression, names),
+            MainOperation::Operator(operator) => operator_names(operator, names),
+            _ => ()
+        }
+    }
+}
+
+fn operator_names(operator: &Operator, names: &mut HashSet<u64>) {
+    match operator {
+        Operator::Composite(block) => block_names(block, names),
+        Operator::Assignment(place, expression) => {
+            place_names(place, names);
+            expression_names(expression, names);
+        },
+        Operator::If(condition, then, otherwise) => {
+            expression_names(condition, names);
+            operator_names(then, names);
+            if let Some(otherwise) = otherwise {
+                operator_names(otherwise, names);
+            }
+        },
+        Operator::For(init, condition, step, body) => {
+            for operator in [init, step].into_iter().flatten() {
+                operator_names(operator, names);
+            }
+            if let Some(condition) = condition {
+                expression_names(condition, names);
+            }
+            operator_names(body, names);
+        },
+        Operator::ForConditions(conditions, body) => {
+            for condition in conditions {
+                expression_names(condition, names);
+            }
+            operator_names(body, names);
+        },
+        Operator::While(condition, body) => {
+            expression_names(condition, names);
+            operator_names(body, names);
+        },
+        Operator::Case(selector, arms, default) => {
+            expression_names(selector, names);
+            for (_, operator) in arms {
+                operator_names(operator, names);
+            }
+            if let Some(default) = default {
+                operator_names(default, names);
+            }
+        },
+        Operator::Input(places) => for place in places {
+            place_names(place, names);
+        },
+        Operator::Output(items) => for item in items {
+            if let OutputItem::Expression(expression) = item {
+                expression_names(expression, names);
+            }
+        },
+        Operator::Call(ident, arguments, _) => {
+            names.insert(ident.id);
+            for argument in arguments {
+                expression_names(argument, names);
+            }
+        },
+        Operator::Return(Some(expression), _) => expression_names(expression, names),
+        Operator::Return(None, _) | Operator::Break(_) | Operator::Continue(_) => ()
+    }
+}
+
+/// Индекс элемента читается, сама переменная - нет
+fn place_names(place: &Place, names: &mut HashSet<u64>) {
+    if let Place::Element(_, index, _) = place {
+        expression_names(index, names);
+    }
+}
+
+fn expression_names(expression: &Expression, names: &mut HashSet<u64>) {
+    for operand in &expression.operands {
+        for term in &operand.terms {
+            for multiplier in &term.multipliers {
+                multiplier_names(multiplier, names);
+            }
+        }
+    }
+}
+
+fn multiplier_names(multiplier: &Multiplier, names: &mut HashSet<u64>) {
+    match multiplier {
+        Multiplier::Identifier(ident) => {
+            names.insert(ident.id);
+        },
+        Multiplier::Not(multiplier, _) | Multiplier::Neg(multiplier, _) => multiplier_names(multiplier, names),
+        Multiplier::Convert(_, expression, _) | Multiplier::Expression(expression) => expression_names(expression, names),
+        Multiplier::Call(ident, arguments, _) => {
+            names.insert(ident.id);
+            for argument in arguments {
+                expression_names(argument, names);
+            }
+        },
+        Multiplier::Index(ident, index, _) => {
+            names.insert(ident.id);
+            expression_names(index, names);
+        },
+        Multiplier::Power(base, exponent, _) => {
+            multiplier_names(base, names);
+            multiplier_names(exponent, names);
+        },
+        Multiplier::Variable(..) | Multiplier::Boolean(..) | Multiplier::Constant(..) => ()
+    }
+}
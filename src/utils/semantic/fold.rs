@@ -0,0 +1,242 @@
+use crate::utils::structs::{program::{Expression, Multiplier, Operand, Term}, span::Span, types::{MultiplicationOperations, ProgramTypes}};
+
+use super::{error::{SemanticError, SemanticResult}, Semantic};
+
+/// Левый операнд цепочки `&&` или `||`: начало его кода, значение, если оно
+/// известно при компиляции, и отсутствие побочных эффектов
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Left {
+    pub start: u64,
+    pub value: Option<bool>,
+    pub pure: bool
+}
+
+/// Упрощение `&&` и `||`, когда значение одного из операндов известно
+pub(super) enum Shortcut {
+    /// Левый операнд не влияет на результат, его код отбрасывается
+    Right,
+    /// Результат определяется левым операндом, правый только проверяется
+    Left,
+    /// Правый операнд не влияет на результат
+    Keep,
+    /// Результат определяется правым операндом
+    Value(bool)
+}
+
+impl Left {
+    /// `absorbing` - значение операнда, при котором второй операнд
+    /// не важен: `false` для `&&` и `true` для `||`
+    pub(super) fn shortcut(&self, absorbing: bool, right: Option<bool>) -> Option<Shortcut> {
+        match (self.value, right) {
+            (Some(value), _) if value == absorbing => Some(Shortcut::Left),
+            (Some(_), _) => Some(Shortcut::Right),
+            (None, Some(value)) if value == absorbing => Some(Shortcut::Value(value)),
+            (None, Some(_)) => Some(Shortcut::Keep),
+            (None, None) => None
+        }
+    }
+}
+
+/// Оптимизации `-O1`: свёртка константных подвыражений, упрощение логических
+/// операций и удаление кода, который никогда не выполняется. Типы и ошибки
+/// проверяются так же, как без оптимизаций
+impl Semantic {
+    /// Вычислимое при компиляции выражение заменяется значением, у остальных
+    /// сворачиваются подвыражения и константное начало цепочки операций
+    pub(super) fn fold(&self, expression: Expression) -> Expression {
+        let span = expression.span;
+        if let Ok(value) = self.evaluate(&expression) {
+            return Expression { operands: vec![constant_operand(value, span)], operations: Vec::new(), span };
+        }
+        let Expression { operands, mut operations, .. } = expression;
+        let mut operands: Vec<_> = operands.into_iter().map(|operand| self.fold_operand(operand)).collect();
+        let known = operands.iter().take_while(|operand| operand_value(operand).is_some()).count();
+        if known > 1 {
+            let prefix_span = operands[0].span.to(operands[known - 1].span);
+            let prefix = Expression { operands: operands[..known].to_vec(), operations: operations[..known - 1].to_vec(), span: prefix_span };
+            if let Ok(value) = self.evaluate(&prefix) {
+                operands.splice(..known, [constant_operand(value, prefix_span)]);
+                operations.drain(..known - 1);
+            }
+        }
+        Expression { operands, operations, span }
+    }
+
+    fn fold_operand(&self, operand: Operand) -> Operand {
+        if let Ok(value) = self.evaluate_operand(&operand) {
+            return constant_operand(value, operand.span);
+        }
+        let Operand { terms, mut operations, span } = operand;
+        let mut terms: Vec<_> = terms.into_iter().map(|term| self.fold_term(term)).collect();
+        let known = terms.iter().take_while(|term| term_value(term).is_some()).count();
+        if known > 1 {
+            let prefix_span = terms[0].span.to(terms[known - 1].span);
+            let prefix = Operand { terms: terms[..known].to_vec(), operations: operations[..known - 1].to_vec(), span: prefix_span };
+            if let Ok(value) = self.evaluate_operand(&prefix) {
+                terms.splice(..known, [constant_term(value, prefix_span)]);
+                operations.drain(..known - 1);
+            }
+        }
+        Operand { terms, operations, span }
+    }
+
+    fn fold_term(&self, term: Term) -> Term {
+        if let Ok(value) = self.evaluate_term(&term) {
+            return constant_term(value, term.span);
+        }
+        let Term { multipliers, mut operations, span } = term;
+        let mut multipliers: Vec<_> = multipliers.into_iter().map(|multiplier| self.fold_multiplier(multiplier)).collect();
+        let known = multipliers.iter().take_while(|multiplier| matches!(multiplier, Multiplier::Constant(..))).count();
+        if known > 1 {
+            let prefix_span = multipliers[0].span().to(multipliers[known - 1].span());
+            let prefix = Term { multipliers: multipliers[..known].to_vec(), operations: operations[..known - 1].to_vec(), span: prefix_span };
+            if let Ok(value) = self.evaluate_term(&prefix) {
+                multipliers.splice(..known, [Multiplier::Constant(value, prefix_span)]);
+                operations.drain(..known - 1);
+            }
+        }
+        Term { multipliers, operations, span }
+    }
+
+    fn fold_multiplier(&self, multiplier: Multiplier) -> Multiplier {
+        if let Ok(value) = self.evaluate_multiplier(&multiplier) {
+            return Multiplier::Constant(value, multiplier.span());
+        }
+        match multiplier {
+            Multiplier::Not(m, span) => Multiplier::Not(Box::new(self.fold_multiplier(*m)), span),
+            Multiplier::Neg(m, span) => Multiplier::Neg(Box::new(self.fold_multiplier(*m)), span),
+            Multiplier::Convert(target, e, span) => Multiplier::Convert(target, self.fold(e), span),
+            Multiplier::Call(ident, arguments, span) =>
+                Multiplier::Call(ident, arguments.into_iter().map(|argument| self.fold(argument)).collect(), span),
+            Multiplier::Index(ident, index, span) => Multiplier::Index(ident, Box::new(self.fold(*index)), span),
+            Multiplier::Power(base, exponent, span) =>
+                Multiplier::Power(Box::new(self.fold_multiplier(*base)), Box::new(self.fold_multiplier(*exponent)), span),
+            Multiplier::Expression(e) => Multiplier::Expression(self.fold(e)),
+            multiplier => multiplier
+        }
+    }
+
+    /// Код правого операнда `&&` или `||` по упрощению `shortcut`,
+    /// возвращает тип правого операнда
+    pub(super) fn simplify(
+        &mut self,
+        shortcut: Shortcut,
+        left: &mut Left,
+        (value, pure): (Option<bool>, bool),
+        test: impl FnOnce(&mut Self) -> SemanticResult<ProgramTypes>
+    ) -> SemanticResult<ProgramTypes> {
+        match shortcut {
+            Shortcut::Right => {
+                self.discard(left.start);
+                *left = Left { start: left.start, value, pure };
+                test(self)
+            },
+            Shortcut::Left => {
+                let mark = self.position;
+                let t = test(self);
+                self.discard(mark);
+                t
+            },
+            Shortcut::Keep => Ok(ProgramTypes::Boolean(None)),
+            Shortcut::Value(value) => {
+                if left.pure {
+                    self.discard(left.start);
+                }
+                self.asm_bool(value);
+                left.value = Some(value);
+                Ok(ProgramTypes::Boolean(None))
+            }
+        }
+    }
+
+    /// Отбрасывает код начиная с `mark` вместе с его переходами и вызовами
+    pub(super) fn discard(&mut self, mark: u64) {
+        self.asm.truncate(mark as usize);
+        self.cur_pos();
+        self.asm_idents.retain(|(_, position, _)| *position < mark);
+        self.calls.retain(|(_, position)| *position < mark);
+        self.bounds.retain(|position| *position < mark);
+        self.divisions.retain(|position| *position < mark);
+        self.powers.retain(|position| *position < mark);
+        for current in &mut self.loops {
+            current.breaks.retain(|position| *position < mark);
+        }
+    }
+}
+
+/// Проверка типов `&&` и `||`, код которых не генерируется
+pub(super) fn logical(left: &ProgramTypes, right: ProgramTypes, operation: String, span: Span, left_span: Span) -> SemanticResult<ProgramTypes> {
+    match (left, right) {
+        (ProgramTypes::Boolean(_), ProgramTypes::Boolean(_)) => Ok(ProgramTypes::Boolean(None)),
+        (left, right @ ProgramTypes::Boolean(_)) => Err(SemanticError::TypeError(right, left.clone(), span, left_span)),
+        (_, right) => Err(SemanticError::InvalidOperation(right, operation, span))
+    }
+}
+
+/// Логическое значение, известное при компиляции
+pub(super) fn boolean(multiplier: &Multiplier) -> Option<bool> {
+    match multiplier {
+        Multiplier::Constant(ProgramTypes::Boolean(Some(value)), _) => Some(*value),
+        _ => None
+    }
+}
+
+pub(super) fn term_boolean(term: &Term) -> Option<bool> {
+    match term.multipliers.as_slice() {
+        [multiplier] => boolean(multiplier),
+        _ => None
+    }
+}
+
+/// Вычисление не имеет побочных эффектов и не завершает программу с ошибкой,
+/// поэтому его код можно отбросить
+pub(super) fn pure(multiplier: &Multiplier) -> bool {
+    match multiplier {
+        Multiplier::Call(..)|Multiplier::Index(..)|Multiplier::Power(..) => false,
+        Multiplier::Not(m, _)|Multiplier::Neg(m, _) => pure(m),
+        Multiplier::Convert(_, e, _)|Multiplier::Expression(e) => e.operands.iter().all(pure_operand),
+        _ => true
+    }
+}
+
+pub(super) fn pure_term(term: &Term) -> bool {
+    term.multipliers.iter().all(pure) && term.operations.iter().all(pure_operation)
+}
+
+/// Деление может завершить программу с ошибкой
+pub(super) fn pure_operation(operation: &MultiplicationOperations) -> bool {
+    matches!(operation, MultiplicationOperations::Multiplication|MultiplicationOperations::And)
+}
+
+fn pure_operand(operand: &Operand) -> bool {
+    operand.terms.iter().all(pure_term)
+}
+
+fn multiplier_value(multiplier: &Multiplier) -> Option<&ProgramTypes> {
+    match multiplier {
+        Multiplier::Constant(value, _) => Some(value),
+        _ => None
+    }
+}
+
+fn term_value(term: &Term) -> Option<&ProgramTypes> {
+    match term.multipliers.as_slice() {
+        [multiplier] => multiplier_value(multiplier),
+        _ => None
+    }
+}
+
+fn operand_value(operand: &Operand) -> Option<&ProgramTypes> {
+    match operand.terms.as_slice() {
+        [term] => term_value(term),
+        _ => None
+    }
+}
+
+fn constant_term(value: ProgramTypes, span: Span) -> Term {
+    Term { multipliers: vec![Multiplier::Constant(value, span)], operations: Vec::new(), span }
+}
+
+fn constant_operand(value: ProgramTypes, span: Span) -> Operand {
+    Operand { terms: vec![constant_term(value, span)], operations: Vec::new(), span }
+}
@@ -0,0 +1,120 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::utils::{messages::Message, structs::span::Span};
+
+/// Именованные предупреждения семантического анализа, уровень каждого
+/// задаётся аргументами `-W`, `-A` и `-D`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    UnusedVariable,
+    UnusedAssignment,
+    UnusedInput,
+    ConstantCondition,
+    InfiniteLoop,
+    UnreachableCode
+}
+
+impl Lint {
+    pub const ALL: [Lint; 6] = [
+        Lint::UnusedVariable,
+        Lint::UnusedAssignment,
+        Lint::UnusedInput,
+        Lint::ConstantCondition,
+        Lint::InfiniteLoop,
+        Lint::UnreachableCode
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "unused-variable",
+            Lint::UnusedAssignment => "unused-assignment",
+            Lint::UnusedInput => "unused-input",
+            Lint::ConstantCondition => "constant-condition",
+            Lint::InfiniteLoop => "infinite-loop",
+            Lint::UnreachableCode => "unreachable-code"
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "W0001",
+            Lint::UnusedAssignment => "W0002",
+            Lint::UnusedInput => "W0003",
+            Lint::ConstantCondition => "W0004",
+            Lint::InfiniteLoop => "W0005",
+            Lint::UnreachableCode => "W0006"
+        }
+    }
+}
+
+impl FromStr for Lint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Lint::ALL.into_iter().find(|lint| lint.name() == s)
+            .ok_or_else(|| Message::UnknownLint(s.into()).to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny
+}
+
+/// Уровни предупреждений. По умолчанию все включены как предупреждения,
+/// `--deny-warnings` превращает их в ошибки
+#[derive(Debug, Clone, Default)]
+pub struct Lints {
+    levels: HashMap<Lint, Level>,
+    pub deny_warnings: bool
+}
+
+impl Lints {
+    pub fn set(&mut self, lint: Lint, level: Level) {
+        self.levels.insert(lint, level);
+    }
+
+    pub fn level(&self, lint: Lint) -> Level {
+        match self.levels.get(&lint) {
+            Some(Level::Warn) | None if self.deny_warnings => Level::Deny,
+            Some(level) => *level,
+            None => Level::Warn
+        }
+    }
+
+    /// Уровень задан явно аргументом `-W`, `-A` или `-D`
+    pub fn explicit(&self, lint: Lint) -> bool {
+        self.levels.contains_key(&lint)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Warning {
+    /// Объявленная переменная нигде не используется
+    UnusedVariable(u64, Span),
+    /// Присвоенное значение затирается или не читается ни на одном пути
+    UnusedAssignment(u64, Span),
+    /// Значение переменной только вводится и не читается
+    UnusedInput(u64, Span, Span),
+    /// Условие `if` или цикла всегда ложно
+    ConstantCondition(Span),
+    /// Цикл с всегда истинным условием без `break` и `return`
+    InfiniteLoop(Span),
+    /// Код после `break`, `continue` или `return` не выполняется
+    UnreachableCode(Span)
+}
+
+impl Warning {
+    pub fn lint(&self) -> Lint {
+        match self {
+            Warning::UnusedVariable(..) => Lint::UnusedVariable,
+            Warning::UnusedAssignment(..) => Lint::UnusedAssignment,
+            Warning::UnusedInput(..) => Lint::UnusedInput,
+            Warning::ConstantCondition(_) => Lint::ConstantCondition,
+            Warning::InfiniteLoop(_) => Lint::InfiniteLoop,
+            Warning::UnreachableCode(_) => Lint::UnreachableCode
+        }
+    }
+}
@@ -1,16 +1,138 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use error::{SemanticError, SemanticResult};
+use error::{Path, SemanticError, SemanticResult};
+use flow::State;
+use fold::Left;
+use lint::Warning;
 
-use crate::utils::structs::types::AdditionOperations;
+use crate::utils::{diagnostic::Phase, messages::Message, structs::types::AdditionOperations};
 
-use super::structs::{program::{Expression, MainOperation, Multiplier, Operand, Operator, Term}, types::{LexerDigitalData, MultiplicationOperations, ProgramTypes, RelationOperations}};
+use super::structs::{program::{Expression, Function, Ident, MainOperation, Multiplier, Operand, Operator, OutputItem, Place, Term}, span::Span, types::{LexerDigitalData, MultiplicationOperations, ProgramTypes, RelationOperations}};
 
 pub mod error;
+pub mod lint;
+mod constant;
+mod flow;
+mod fold;
+
+/// Цикл, внутри которого генерируется код: `continue` переходит
+/// к проверке условия, переходы `break` дописываются после тела.
+/// Состояния присваиваний в местах `break` и `continue` сливаются после цикла
+struct Loop {
+    start: u64,
+    breaks: Vec<u64>,
+    exits: Vec<(State, Path)>,
+    continues: Vec<State>,
+    /// В теле есть `return`, через который цикл тоже завершается
+    returns: bool
+}
+
+/// Переходы к ветвям `case`: позиции rel32 с номером ветви (`None` - ветвь
+/// `else`) и таблица смещений в `.text` с номером ветви для каждого значения
+struct Dispatch {
+    jumps: Vec<(u64, Option<usize>)>,
+    table: Option<(u64, Vec<Option<usize>>)>
+}
+
+/// Составной оператор с объявлениями. Прежние привязки перекрытых имён
+/// восстанавливаются на выходе, а место в кадре стека освобождается
+struct Scope {
+    shadowed: Vec<Shadowed>,
+    frame: i32
+}
+
+/// Имя, объявленное в блоке, и его привязка во внешней области видимости
+struct Shadowed {
+    id: u64,
+    t: Option<ProgramTypes>,
+    constant: Option<ProgramTypes>,
+    declaration: Option<Span>,
+    local: Option<i32>,
+    path: Option<Path>,
+    variable: Option<Span>
+}
+
+/// Использование объявленной переменной: чтение, присваивание и последний ввод
+#[derive(Debug, Clone, Copy, Default)]
+struct Usage {
+    read: bool,
+    assigned: bool,
+    input: Option<Span>
+}
+
+/// Сигнатура процедуры или функции и начало её кода
+struct Signature {
+    params: Vec<(Ident, ProgramTypes)>,
+    result: Option<ProgramTypes>,
+    span: Span,
+    position: u64
+}
 
 pub struct Semantic {
     program: Vec<MainOperation>,
     identifiers: HashMap<u64, ProgramTypes>,
+    /// Значения именованных констант, в коде они подставляются непосредственно
+    constants: HashMap<u64, ProgramTypes>,
+    /// Имена, объявленные только константами: ячейка в `.data` им не нужна
+    pub constant_ids: HashSet<u64>,
+    declarations: HashMap<u64, Span>,
+    errors: Vec<SemanticError>,
+    loops: Vec<Loop>,
+    functions: HashMap<u64, Signature>,
+    /// Места вызовов `call rel32`, которые заполняются после генерации всех функций
+    calls: Vec<(u64, u64)>,
+    /// Смещения параметров и локальных переменных текущей функции относительно rbp
+    locals: HashMap<u64, i32>,
+    /// Занятая часть кадра текущей функции в байтах
+    frame: i32,
+    /// Размер кадра: наибольшая занятая часть с учётом вложенных блоков
+    frame_size: i32,
+    scopes: Vec<Scope>,
+    /// Пути, на которых переменные без значения могли его получить
+    paths: HashMap<u64, Path>,
+    /// Текущая точка достижима: до неё не было `break`, `continue` или `return`
+    reachable: bool,
+    /// Оператор, после которого код недостижим, для предупреждения
+    terminator: Option<Span>,
+    /// Место объявления переменной, с которой сейчас связано имя
+    variables: HashMap<u64, Span>,
+    /// Использование переменных по месту объявления
+    usage: HashMap<Span, Usage>,
+    /// Присваивания, значение которых может дойти до текущей точки,
+    /// по месту объявления переменной
+    stores: HashMap<Span, Vec<Span>>,
+    /// Объявления переменных в порядке чтения, по ним находятся
+    /// переменные, прочитанные внутри цикла
+    loads: Vec<Span>,
+    /// Присваивания, значение которых хотя бы где-то читается
+    used: HashSet<Span>,
+    /// Присваивания переменным в порядке следования
+    assignments: Vec<(u64, Span)>,
+    /// Места объявления глобальных переменных
+    globals: HashMap<u64, Span>,
+    /// Имена, которые могут прочитать подпрограмма и вызванные из неё
+    reads: HashMap<u64, HashSet<u64>>,
+    /// Переменные в порядке объявления
+    declared: Vec<(u64, Span)>,
+    pub warnings: Vec<Warning>,
+    function: Option<u64>,
+    /// Места вызова подпрограммы, которая сообщает о выходе за границы массива
+    bounds: Vec<u64>,
+    /// Места вызова подпрограммы, которая проверяет делитель 0 и -1
+    divisions: Vec<u64>,
+    /// Места вызова подпрограммы целочисленного возведения в степень
+    powers: Vec<u64>,
+    /// Глобальные массивы и их длины в порядке объявления
+    pub arrays: Vec<(u64, u64)>,
+    /// В код добавлена проверка границ, которой нужна строка сообщения
+    pub bounds_checked: bool,
+    /// В код добавлена проверка деления, которой нужна строка сообщения
+    pub division_checked: bool,
+    /// Программа разобрана с ошибками: часть присваиваний могла потеряться,
+    /// поэтому об использовании неинициализированных переменных не сообщаем
+    pub partial: bool,
+    /// Оптимизации `-O1`
+    pub optimize: bool,
     pub reserve: u64,
     vars: HashMap<u64, LexerDigitalData>,
     pub asm: Vec<u8>,
@@ -23,6 +145,39 @@ impl Semantic {
         Self {
             program,
             identifiers: HashMap::new(),
+            constants: HashMap::new(),
+            constant_ids: HashSet::new(),
+            declarations: HashMap::new(),
+            errors: Vec::new(),
+            loops: Vec::new(),
+            functions: HashMap::new(),
+            calls: Vec::new(),
+            locals: HashMap::new(),
+            frame: 0,
+            frame_size: 0,
+            scopes: Vec::new(),
+            paths: HashMap::new(),
+            reachable: true,
+            terminator: None,
+            variables: HashMap::new(),
+            usage: HashMap::new(),
+            stores: HashMap::new(),
+            loads: Vec::new(),
+            used: HashSet::new(),
+            assignments: Vec::new(),
+            globals: HashMap::new(),
+            reads: HashMap::new(),
+            declared: Vec::new(),
+            warnings: Vec::new(),
+            function: None,
+            bounds: Vec::new(),
+            divisions: Vec::new(),
+            powers: Vec::new(),
+            arrays: Vec::new(),
+            bounds_checked: false,
+            division_checked: false,
+            partial: false,
+            optimize: false,
             vars,
             asm: Vec::new(),
             position: 0,
@@ -31,218 +186,942 @@ impl Semantic {
         }
     }
 
-    pub fn run_process(&mut self) -> SemanticResult<()> {
-        self.asm.append(&mut vec![0x48, 0x83, 0xec, 0x08]); // sub rsp, 8
-        self.cur_pos();
+    pub fn run_process(&mut self) -> Result<(), Vec<SemanticError>> {
+        // переменные вложенных блоков основной программы лежат в её кадре
+        let frame_position = self.enter();
+
+        // сигнатуры известны до генерации кода, поэтому вызов
+        // может стоять раньше объявления и функция может быть рекурсивной
+        for main_operation in &self.program {
+            if let MainOperation::Function(function) = main_operation {
+                match self.declarations.get(&function.ident.id) {
+                    None => {
+                        self.declarations.insert(function.ident.id, function.ident.span);
+                        self.functions.insert(function.ident.id, Signature {
+                            params: function.params.clone(),
+                            result: function.result.clone(),
+                            span: function.ident.span,
+                            position: 0
+                        });
+                    },
+                    Some(first) => self.errors.push(
+                        SemanticError::IdentifierAlreadyDeclared(function.ident.id, function.ident.span, *first)
+                    )
+                }
+            }
+        }
 
+        self.reads = flow::reads(&self.program);
+        let mut functions = Vec::new();
         for main_operation in self.program.clone() {
             match main_operation {
-                MainOperation::CreateVariable(ident_vec) =>
-                    for (identifiers, identifiers_type) in ident_vec {
-                        for id in identifiers {
-                            match self.identifiers.get(&id) {
-                                None => self.identifiers.insert(id, identifiers_type.clone()),
-                                Some(_) => return Err(SemanticError::IdentifierAlreadyDeclared(id))
-                            };
-                        }
-                    },
+                MainOperation::CreateVariable(ident_vec) => self.declare(ident_vec),
+                MainOperation::CreateConstant(ident, expression) => self.declare_constant(ident, expression),
                 MainOperation::Operator(operator) => {
-                    match self.test_operator(operator) {
-                        Ok(_) => (),
-                        Err(e) => return Err(e.clone())
-                    }
-                }
+                    self.check_reachable();
+                    let res = self.test_operator(operator);
+                    self.record(res);
+                },
+                MainOperation::Function(function) => functions.push(function)
             }
         }
 
-        self.asm.append(&mut vec![0xb8, 0x3c, 0x00, 0x00, 0x00, 0x48, 0x31, 0xff, 0x0f, 0x05]);
+        // exit(0) из libc, а не системный вызов: иначе буфер stdout
+        // теряется, когда вывод программы перенаправлен
+        self.asm.append(&mut vec![0x31, 0xff, 0xe8, 0x00, 0x00, 0x00, 0x00]);
         self.cur_pos();
+        self.asm_idents.push((self.reserve+7, self.position - 4, false));
+        self.patch_frame(frame_position);
 
-        Ok(())
+        for function in functions {
+            self.test_function(function);
+        }
+        for (id, call_position) in std::mem::take(&mut self.calls) {
+            let target = self.functions[&id].position;
+            self.patch_rel32(call_position, target);
+        }
+        if !self.bounds.is_empty() {
+            self.bounds_routine();
+        }
+        // ноль в отрицательной степени - тоже деление на ноль
+        if !self.divisions.is_empty() || !self.powers.is_empty() {
+            let failure = self.division_routine();
+            if !self.powers.is_empty() {
+                self.power_routine(failure);
+            }
+        }
+        self.unused();
+        let asm_idents = &self.asm_idents;
+        self.constant_ids.retain(|id| !asm_idents.iter().any(|(i, _, _)| i == id));
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
     }
 
-    fn test_operator(&mut self, operator: Operator) -> SemanticResult<()> {
-        match operator {
-            Operator::Assignment(id, expression) => match self.test_expression(expression) {
-                Ok(t) => match self.identifiers.get(&id) {
-                    Some(v) => match t.clone() & v.clone() {
-                        true => {
-                            match v {
-                                ProgramTypes::Boolean(_) => {
-                                    let i_type = ProgramTypes::Boolean(Some(false));
-                                    self.identifiers.insert(id, i_type.clone());
-                                },
-                                ProgramTypes::Float(_) => {
-                                    let i_type = ProgramTypes::Float(Some(0.));
-                                    self.identifiers.insert(id, i_type.clone());
-                                },
-                                ProgramTypes::Integer(_) => {
-                                    let i_type = ProgramTypes::Integer(Some(0));
-                                    self.identifiers.insert(id, i_type.clone());
-                                },
+    /// Объявление переменных. Внутри функции и во вложенных блоках переменные
+    /// размещаются в кадре стека и обнуляются в месте объявления
+    fn declare(&mut self, ident_vec: Vec<(Vec<Ident>, ProgramTypes)>) {
+        for (identifiers, identifiers_type) in ident_vec {
+            for ident in identifiers {
+                if !self.bind(ident) {
+                    continue;
+                }
+                self.identifiers.insert(ident.id, identifiers_type.clone());
+                self.variables.insert(ident.id, ident.span);
+                self.declared.push((ident.id, ident.span));
+                if self.function.is_none() && self.scopes.is_empty() {
+                    self.globals.insert(ident.id, ident.span);
+                }
+                let len = match &identifiers_type {
+                    ProgramTypes::Array(_, len) => Some(*len),
+                    _ => None
+                };
+                let local = self.function.is_some() || !self.scopes.is_empty();
+                match (local, len) {
+                    (true, len) => match self.allocate(8 * len.unwrap_or(1)) {
+                        Some(offset) => {
+                            self.locals.insert(ident.id, offset);
+                            match len {
+                                Some(len) => self.zero_local_array(offset, len),
+                                None => self.mov_local_zero(offset)
                             }
-                            self.assign(id);
-                            Ok(())
                         },
-                        _ => return Err(SemanticError::AssignError(t, v.clone()))
+                        None => self.errors.push(SemanticError::StorageSize(ident.span))
+                    },
+                    (false, Some(len)) => {
+                        // `.bss` адресуется 32-битным смещением со знаком
+                        let storage: u64 = self.arrays.iter().map(|(_, len)| 8 * len).sum();
+                        if storage + 8 * len > i32::MAX as u64 {
+                            self.errors.push(SemanticError::StorageSize(ident.span));
+                        }
+                        self.arrays.push((ident.id, len));
                     },
-                    None => return Err(SemanticError::NotDefined(id))
+                    (false, None) => ()
+                }
+            }
+        }
+    }
+
+    /// Константа вычисляется при компиляции и места в памяти не занимает.
+    /// Значение вычисляется до объявления, поэтому может ссылаться
+    /// на одноимённую константу внешнего блока
+    fn declare_constant(&mut self, ident: Ident, expression: Expression) {
+        match self.evaluate(&expression) {
+            Ok(value) => if self.bind(ident) {
+                self.constants.insert(ident.id, value);
+                self.constant_ids.insert(ident.id);
+            },
+            Err(e) => self.errors.push(e)
+        }
+    }
+
+    /// Связывает имя с новым объявлением. Прежняя привязка убирается,
+    /// а во вложенном блоке запоминается для восстановления на выходе
+    fn bind(&mut self, ident: Ident) -> bool {
+        if let Some(first) = self.redeclaration(ident.id) {
+            self.errors.push(SemanticError::IdentifierAlreadyDeclared(ident.id, ident.span, first));
+            return false;
+        }
+        let shadowed = Shadowed {
+            id: ident.id,
+            t: self.identifiers.remove(&ident.id),
+            constant: self.constants.remove(&ident.id),
+            declaration: self.declarations.insert(ident.id, ident.span),
+            local: self.locals.remove(&ident.id),
+            path: self.paths.remove(&ident.id),
+            variable: self.variables.remove(&ident.id)
+        };
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.shadowed.push(shadowed);
+        }
+        true
+    }
+
+    /// Первое объявление имени, которое нельзя перекрыть: во вложенном
+    /// блоке запрещено только повторное объявление в том же блоке
+    fn redeclaration(&self, id: u64) -> Option<Span> {
+        match self.scopes.last() {
+            Some(scope) if !scope.shadowed.iter().any(|s| s.id == id) => None,
+            _ => self.declarations.get(&id).copied()
+        }
+    }
+
+    /// Место в кадре стека, возвращает смещение относительно rbp.
+    /// `None`, если кадр вместе с вложенными блоками и выравниванием
+    /// до 16 байт не помещается в 32-битное число со знаком
+    fn allocate(&mut self, size: u64) -> Option<i32> {
+        let frame = i32::try_from(size).ok()
+            .and_then(|size| self.frame.checked_add(size))
+            .filter(|frame| *frame <= i32::MAX & !15)?;
+        self.frame = frame;
+        self.frame_size = self.frame_size.max(frame);
+        Some(-frame)
+    }
+
+    /// Составной оператор со своей областью видимости
+    fn test_scope(&mut self, block: Vec<MainOperation>) {
+        self.scopes.push(Scope { shadowed: Vec::new(), frame: self.frame });
+        for main_operation in block {
+            match main_operation {
+                MainOperation::CreateVariable(ident_vec) => self.declare(ident_vec),
+                MainOperation::CreateConstant(ident, expression) => self.declare_constant(ident, expression),
+                MainOperation::Operator(operator) => {
+                    self.check_reachable();
+                    let res = self.test_operator(operator);
+                    self.record(res);
                 },
-                Err(e) => return Err(e)
+                MainOperation::Function(_) => ()
+            }
+        }
+
+        let scope = self.scopes.pop().unwrap();
+        for Shadowed { id, t, constant, declaration, local, path, variable } in scope.shadowed.into_iter().rev() {
+            restore(&mut self.identifiers, id, t);
+            restore(&mut self.paths, id, path);
+            restore(&mut self.variables, id, variable);
+            restore(&mut self.constants, id, constant);
+            restore(&mut self.declarations, id, declaration);
+            restore(&mut self.locals, id, local);
+        }
+        self.frame = scope.frame;
+    }
+
+    /// Ошибка в использовании имени, которое перекрывает объявленное во внешней
+    /// области видимости, дополняется местом внешнего объявления
+    fn shadowing(&self, id: u64, e: SemanticError) -> SemanticError {
+        let outer = self.scopes.iter().rev()
+            .find_map(|scope| scope.shadowed.iter().find(|s| s.id == id))
+            .and_then(|s| s.declaration);
+        match outer {
+            Some(outer) => SemanticError::Shadowing(Box::new(e), id, outer),
+            None => e
+        }
+    }
+
+    /// Код процедуры или функции. Параметры лежат в стеке вызывающего,
+    /// глобальные переменные видны, но локальные имена их перекрывают
+    fn test_function(&mut self, function: Function) {
+        let Function { ident, params, body, .. } = function;
+        if let Some(signature) = self.functions.get_mut(&ident.id) {
+            signature.position = self.position;
+        }
+
+        let frame_position = self.enter();
+
+        // функция может быть вызвана в любом месте программы,
+        // поэтому глобальные переменные считаются инициализированными
+        let globals = self.identifiers.clone();
+        for (id, t) in &globals {
+            self.initialize(*id, t);
+        }
+        let constants = self.constants.clone();
+        let declarations = std::mem::take(&mut self.declarations);
+        let paths = std::mem::take(&mut self.paths);
+        let reachable = std::mem::replace(&mut self.reachable, true);
+        let variables = self.variables.clone();
+        self.function = Some(ident.id);
+        // чтение функцией присваиваний основной программы учтено в местах вызова
+        self.stores.clear();
+
+        let count = params.len() as i32;
+        for (i, (param, param_type)) in params.into_iter().enumerate() {
+            match self.declarations.get(&param.id) {
+                None => {
+                    self.declarations.insert(param.id, param.span);
+                    self.locals.insert(param.id, 16 + 8 * (count - 1 - i as i32));
+                    self.variables.remove(&param.id);
+                    self.initialize(param.id, &param_type);
+                },
+                Some(first) => self.errors.push(
+                    SemanticError::IdentifierAlreadyDeclared(param.id, param.span, *first)
+                )
+            }
+        }
+
+        for main_operation in body {
+            match main_operation {
+                MainOperation::CreateVariable(ident_vec) => self.declare(ident_vec),
+                MainOperation::CreateConstant(ident, expression) => self.declare_constant(ident, expression),
+                MainOperation::Operator(operator) => {
+                    self.check_reachable();
+                    let res = self.test_operator(operator);
+                    self.record(res);
+                },
+                MainOperation::Function(_) => ()
+            }
+        }
+        // без return функция возвращает ноль
+        self.asm.append(&mut vec![0x31, 0xc0]);
+        self.leave();
+        self.escape(None);
+
+        self.patch_frame(frame_position);
+
+        self.identifiers = globals;
+        self.constants = constants;
+        self.declarations = declarations;
+        self.paths = paths;
+        self.reachable = reachable;
+        self.variables = variables;
+        self.locals.clear();
+        self.function = None;
+    }
+
+    /// Вызов с аргументами в стеке, результат функции остаётся в rax
+    fn test_call(&mut self, ident: Ident, arguments: Vec<Expression>, span: Span) -> SemanticResult<Option<ProgramTypes>> {
+        let (params, result, declaration) = match self.functions.get(&ident.id) {
+            Some(signature) => (signature.params.clone(), signature.result.clone(), signature.span),
+            None => return Err(SemanticError::NotDefined(ident.id, ident.span))
+        };
+        if params.len() != arguments.len() {
+            return Err(SemanticError::ArgumentCount(ident.id, params.len(), arguments.len(), span, declaration));
+        }
+
+        for (argument, (param, param_type)) in arguments.into_iter().zip(params.iter()) {
+            let argument_span = argument.span;
+            let t = self.test_expression(argument)?;
+            match (&t, param_type) {
+                (ProgramTypes::Integer(_), ProgramTypes::Float(_)) => self.int_to_float_rax(),
+                (t, p) if t.clone() & p.clone() => (),
+                _ => return Err(SemanticError::AssignError(t, param_type.clone(), argument_span, param.span))
+            }
+            self.push_rax();
+        }
+
+        self.asm.append(&mut vec![0xe8, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.calls.push((ident.id, self.position - 4));
+        self.escape(Some(ident.id));
+        if !params.is_empty() {
+            // add rsp, imm32
+            self.asm.append(&mut vec![0x48, 0x81, 0xc4]);
+            self.asm.append(&mut (8 * params.len() as u32).to_le_bytes().to_vec());
+            self.cur_pos();
+        }
+        Ok(result)
+    }
+
+    fn test_operator(&mut self, operator: Operator) -> SemanticResult<()> {
+        match operator {
+            Operator::Assignment(Place::Element(ident, index, place_span), expression) => {
+                let span = expression.span;
+                self.mark(ident.id, |usage| usage.assigned = true);
+                let v = self.test_element(ident, index, place_span)?;
+                self.push_rbx();
+                let t = self.test_expression(expression)?;
+                self.store_element(ident.id, place_span);
+                match (&t, &v) {
+                    (ProgramTypes::Integer(_), ProgramTypes::Float(_)) => self.int_to_float_rax(),
+                    (t, v) if t.clone() & v.clone() => (),
+                    _ => return Err(SemanticError::AssignError(t, v, span, place_span))
+                }
+                // pop rbx: адрес элемента
+                self.asm.push(0x5b);
+                self.mov_element_rax();
+                Ok(())
             },
-            Operator::Composite(operators) => {
-                for operator in operators {
-                    self.test_operator(operator)?;
+            Operator::Assignment(Place::Variable(ident), expression) => {
+                let span = expression.span;
+                let res = self.test_expression(expression);
+                if self.constants.contains_key(&ident.id) {
+                    self.record(res.map(|_| ()));
+                    return Err(SemanticError::ConstantChange(ident.id, ident.span, self.declarations[&ident.id]));
                 }
-                return Ok(())
+                let v = match self.identifiers.get(&ident.id) {
+                    Some(v) => v.clone(),
+                    None => {
+                        self.record(res.map(|_| ()));
+                        return Err(SemanticError::NotDefined(ident.id, ident.span));
+                    }
+                };
+                if let ProgramTypes::Array(..) = v {
+                    self.record(res.map(|_| ()));
+                    return Err(self.shadowing(ident.id, SemanticError::ArrayUsage(ident.id, ident.span)));
+                }
+                // переменная считается инициализированной даже при ошибке в выражении,
+                // чтобы не получать каскад сообщений о её использовании
+                self.initialize(ident.id, &v);
+                self.mark(ident.id, |usage| usage.assigned = true);
+                self.store(ident.id, Some(ident.span));
+                let t = res?;
+                match (&t, &v) {
+                    (ProgramTypes::Integer(_), ProgramTypes::Float(_)) => self.int_to_float_rax(),
+                    (t, v) if t.clone() & v.clone() => (),
+                    _ => return Err(self.shadowing(ident.id, SemanticError::AssignError(t, v, span, ident.span)))
+                }
+                self.assign(ident.id);
+                Ok(())
+            },
+            Operator::Composite(block) => {
+                self.test_scope(block);
+                Ok(())
             },
-            Operator::Output(expressions) => {
-                for expression in expressions {
-                    let p_type = self.test_expression(expression)?;
-                    self.print(p_type);
+            Operator::Output(items) => {
+                for item in items {
+                    match item {
+                        OutputItem::Expression(expression) => match self.test_expression(expression) {
+                            Ok(p_type) => self.print(p_type),
+                            Err(e) => self.errors.push(e)
+                        },
+                        OutputItem::Text(id, _) => self.print_text(id)
+                    }
                 }
-                return Ok(())
+                Ok(())
             },
-            Operator::Input(ids) => {
-                for id in ids {
-                    match self.identifiers.get(&id) {
-                        Some(v) => {
-                            match v {
-                                ProgramTypes::Boolean(_) => {
-                                    let i_type = ProgramTypes::Boolean(Some(false));
-                                    self.identifiers.insert(id, i_type.clone());
-                                    self.input(id, i_type);
-                                },
-                                ProgramTypes::Float(_) => {
-                                    let i_type = ProgramTypes::Float(Some(0.));
-                                    self.identifiers.insert(id, i_type.clone());
-                                    self.input(id, i_type);
-                                },
-                                ProgramTypes::Integer(_) => {
-                                    let i_type = ProgramTypes::Integer(Some(0));
-                                    self.identifiers.insert(id, i_type.clone());
-                                    self.input(id, i_type);
-                                },
+            Operator::Input(places) => {
+                for place in places {
+                    match place {
+                        Place::Element(ident, index, span) => {
+                            self.mark(ident.id, |usage| usage.input = Some(span));
+                            match self.test_element(ident, index, span) {
+                                Ok(t) => self.input_element(t),
+                                Err(e) => self.errors.push(e)
                             }
                         },
-                        None => return Err(SemanticError::NotDefined(id))
+                        Place::Variable(ident) if self.constants.contains_key(&ident.id) => self.errors.push(
+                            SemanticError::ConstantChange(ident.id, ident.span, self.declarations[&ident.id])
+                        ),
+                        Place::Variable(ident) => match self.identifiers.get(&ident.id) {
+                            Some(ProgramTypes::Array(..)) => self.errors.push(self.shadowing(ident.id, SemanticError::ArrayUsage(ident.id, ident.span))),
+                            Some(v) => {
+                                let i_type = self.initialize(ident.id, &v.clone());
+                                self.mark(ident.id, |usage| usage.input = Some(ident.span));
+                                self.store(ident.id, None);
+                                self.input(ident.id, i_type);
+                            },
+                            None => self.errors.push(SemanticError::NotDefined(ident.id, ident.span))
+                        }
                     };
                 };
-                return Ok(())
+                Ok(())
             },
             Operator::If(expression, operator1, operator2) => {
-                let p_type = self.test_expression(expression)?;
-                match p_type {
-                    ProgramTypes::Boolean(_) => (),
-                    t => return Err(SemanticError::NotBoolean(t))
+                let span = expression.span;
+                self.constant_condition(&expression);
+                // при `-O1` с известным значением условия остаётся код одной ветви,
+                // другая только проверяется
+                let known = if self.optimize { self.condition_value(&expression) } else { None };
+                let mark = self.position;
+                let res = self.test_condition(expression);
+                self.record(res);
+                if known.is_some() {
+                    self.discard(mark);
                 }
-
-                let jz_position = self.jz_default();
-                self.test_operator(*operator1)?;
+                let before = self.state();
+
+                let jz_position = known.is_none().then(|| self.jz_default());
+                let mark = self.position;
+                let res = self.test_operator(*operator1);
+                self.record(res);
+                if known == Some(false) {
+                    self.discard(mark);
+                }
+                let then = self.state();
+                self.set_state(before);
 
                 match operator2 {
                     Some(operator2) => {
-                        let jmp_position = self.jpm_default();
-                        self.jz(jz_position);
-                        self.test_operator(*operator2)?;
-                        self.jmp(jmp_position);
+                        let jmp_position = known.is_none().then(|| self.jpm_default());
+                        if let Some(jz_position) = jz_position {
+                            self.jz(jz_position);
+                        }
+                        let mark = self.position;
+                        let res = self.test_operator(*operator2);
+                        self.record(res);
+                        if known == Some(true) {
+                            self.discard(mark);
+                        }
+                        if let Some(jmp_position) = jmp_position {
+                            self.jmp(jmp_position);
+                        }
                     },
-                    None => {
+                    None => if let Some(jz_position) = jz_position {
                         self.jz(jz_position);
                     }
                 }
 
-                return Ok(())
+                let otherwise = self.state();
+                self.set_state(Self::merge(vec![(then, Path::Then(span)), (otherwise, Path::Else(span))]));
+                Ok(())
             }
-            Operator::For(expressions, operator) => {
+            Operator::For(init, condition, step, operator) => {
+                if let Some(init) = init {
+                    let res = self.test_operator(*init);
+                    self.record(res);
+                }
+                let before = self.state();
+                let loads = self.loads.len();
+                let span = condition.as_ref().map(|condition| condition.span);
+                let endless = match &condition {
+                    Some(condition) => self.constant_condition(condition),
+                    None => true
+                };
+                // шаг стоит перед условием, чтобы `continue` переходил к нему
+                // назад; при входе в цикл шаг перепрыгивается
+                let jmp_position = step.is_some().then(|| self.jpm_default());
                 let start_position = self.position;
-                let mut expressions = expressions.iter();
-
-                if expressions.len() == 0 {
-                    self.asm_bool(true);
-                } else {
-                    let p_type = self.test_expression(expressions.next().unwrap().clone())?;
-                    match p_type {
-                        ProgramTypes::Boolean(_) => (),
-                        t => return Err(SemanticError::NotBoolean(t))
-                    };
-
-                    for expression in expressions {
-                        self.push_rax();
-                        let p_type = self.test_expression(expression.clone())?;
-
-                        match p_type {
-                            ProgramTypes::Boolean(_) => (),
-                            t => return Err(SemanticError::NotBoolean(t))
-                        };
-
-                        self.pop_rbx();
-                        self.cmp();
-                        self.and();
+                // шаг выполняется после тела, но перед первой проверкой
+                // условия его присваиваний ещё не было
+                let mut after_step = None;
+                if let Some(step) = step {
+                    let res = self.test_operator(*step);
+                    self.record(res);
+                    after_step = Some(self.state());
+                    self.set_state(before.clone());
+                }
+                if let Some(jmp_position) = jmp_position {
+                    self.jmp(jmp_position);
+                }
+                match condition {
+                    Some(condition) => {
+                        let res = self.test_condition(condition);
+                        self.record(res);
+                    },
+                    None => self.asm_bool(true)
+                }
+                let jz_position = self.jz_default();
+                self.test_loop_body(*operator, start_position);
+                self.jz(jz_position);
+                self.leave_loop(before, loads, span, endless);
+                if let Some(state) = after_step {
+                    self.backedge(&state, loads);
+                }
+                Ok(())
+            },
+            Operator::ForConditions(expressions, operator) => {
+                let before = self.state();
+                let loads = self.loads.len();
+                let span = expressions.first().zip(expressions.last()).map(|(first, last)| first.span.to(last.span));
+                let mut endless = true;
+                for expression in &expressions {
+                    endless &= self.constant_condition(expression);
+                }
+                let start_position = self.position;
+                let mut expressions = expressions.into_iter();
+
+                match expressions.next() {
+                    None => self.asm_bool(true),
+                    Some(expression) => {
+                        let res = self.test_condition(expression);
+                        self.record(res);
+
+                        for expression in expressions {
+                            self.push_rax();
+                            let res = self.test_condition(expression);
+                            self.record(res);
+                            self.pop_rbx();
+                            self.cmp();
+                            self.and();
+                        }
                     }
                 }
                 let jz_position = self.jz_default();
-                self.test_operator(*operator)?;
-                self.jmp_cycle(start_position);
+                self.test_loop_body(*operator, start_position);
                 self.jz(jz_position);
-                return Ok(())
+                self.leave_loop(before, loads, span, endless);
+                Ok(())
             },
             Operator::While(expression, operator) => {
+                let before = self.state();
+                let loads = self.loads.len();
+                let span = expression.span;
+                let endless = self.constant_condition(&expression);
+                // при `-O1` известное условие не проверяется в коде,
+                // а цикл с ложным условием отбрасывается целиком
+                let known = if self.optimize { self.condition_value(&expression) } else { None };
                 let start_position = self.position;
-                let p_type = self.test_expression(expression.clone())?;
-                match p_type {
-                    ProgramTypes::Boolean(_) => (),
-                    t => return Err(SemanticError::NotBoolean(t))
+                let res = self.test_condition(expression);
+                self.record(res);
+                if known.is_some() {
+                    self.discard(start_position);
+                }
+                let jz_position = known.is_none().then(|| self.jz_default());
+                self.test_loop_body(*operator, start_position);
+                if let Some(jz_position) = jz_position {
+                    self.jz(jz_position);
+                }
+                self.leave_loop(before, loads, Some(span), endless);
+                if known == Some(false) {
+                    self.discard(start_position);
+                }
+                Ok(())
+            },
+            Operator::Case(selector, arms, default) => {
+                let span = selector.span;
+                match self.test_expression(selector) {
+                    Ok(ProgramTypes::Integer(_)) => (),
+                    Ok(t) => self.errors.push(SemanticError::CaseType(t, span)),
+                    Err(e) => self.errors.push(e)
+                }
+                let labels = self.case_labels(&arms);
+                let Dispatch { jumps, table } = self.case_dispatch(&labels);
+                let before = self.state();
+
+                let mut starts = Vec::new();
+                let mut ends = Vec::new();
+                let mut branches = Vec::new();
+                for (labels, operator) in arms {
+                    let labels_span = labels[0].span.to(labels[labels.len() - 1].span);
+                    starts.push(self.position);
+                    self.set_state(before.clone());
+                    let res = self.test_operator(operator);
+                    self.record(res);
+                    ends.push(self.jpm_default());
+                    branches.push((self.state(), Path::Arm(labels_span)));
+                }
+                let default_start = self.position;
+                self.set_state(before);
+                if let Some(default) = default {
+                    let res = self.test_operator(*default);
+                    self.record(res);
+                }
+                branches.push((self.state(), Path::NoLabel(span)));
+                self.set_state(Self::merge(branches));
+
+                let target = |arm: Option<usize>| arm.map_or(default_start, |arm| starts[arm]);
+                for (position, arm) in jumps {
+                    self.patch_rel32(position, target(arm));
+                }
+                if let Some((table_start, entries)) = table {
+                    for (i, arm) in entries.into_iter().enumerate() {
+                        let offset = (target(arm) as i64 - table_start as i64) as i32;
+                        let position = table_start as usize + 4 * i;
+                        self.asm[position..position + 4].copy_from_slice(&offset.to_le_bytes());
+                    }
+                }
+                for position in ends {
+                    self.jmp(position);
+                }
+                Ok(())
+            },
+            Operator::Break(span) => {
+                if self.loops.is_empty() {
+                    return Err(SemanticError::OutsideLoop("break", span));
+                }
+                let jmp_position = self.jpm_default();
+                let state = self.state();
+                let current = self.loops.last_mut().unwrap();
+                current.breaks.push(jmp_position);
+                current.exits.push((state, Path::Break(span)));
+                self.reachable = false;
+                self.terminator = Some(span);
+                Ok(())
+            },
+            Operator::Continue(span) => match self.loops.last() {
+                Some(current) => {
+                    let start = current.start;
+                    self.jmp_cycle(start);
+                    let state = self.state();
+                    self.loops.last_mut().unwrap().continues.push(state);
+                    self.reachable = false;
+                    self.terminator = Some(span);
+                    Ok(())
+                },
+                None => Err(SemanticError::OutsideLoop("continue", span))
+            },
+            Operator::Call(ident, arguments, span) => {
+                self.test_call(ident, arguments, span)?;
+                Ok(())
+            },
+            Operator::Return(expression, span) => {
+                let id = match self.function {
+                    Some(id) => id,
+                    None => return Err(SemanticError::OutsideFunction(span))
                 };
-                let jz_position = self.jz_default();
-                self.test_operator(*operator)?;
-                self.jmp_cycle(start_position);
-                self.jz(jz_position);
-                return Ok(())
+                let signature = &self.functions[&id];
+                let (result, declaration) = (signature.result.clone(), signature.span);
+                match (expression, result) {
+                    (Some(expression), Some(result)) => {
+                        let expression_span = expression.span;
+                        let t = self.test_expression(expression)?;
+                        match (&t, &result) {
+                            (ProgramTypes::Integer(_), ProgramTypes::Float(_)) => self.int_to_float_rax(),
+                            (t, r) if t.clone() & r.clone() => (),
+                            _ => return Err(SemanticError::AssignError(t, result, expression_span, declaration))
+                        }
+                    },
+                    (None, None) => (),
+                    (_, result) => return Err(SemanticError::ReturnValue(id, result.is_some(), span))
+                }
+                self.leave();
+                self.escape(None);
+                for current in &mut self.loops {
+                    current.returns = true;
+                }
+                self.reachable = false;
+                self.terminator = Some(span);
+                Ok(())
+            }
+        }
+    }
+
+    /// Значения меток `case` с номерами ветвей. Метки вычисляются при
+    /// компиляции, повторные и нецелые метки попадают в ошибки
+    fn case_labels(&mut self, arms: &[(Vec<Expression>, Operator)]) -> Vec<(i64, usize)> {
+        let mut labels: Vec<(i64, usize)> = Vec::new();
+        let mut first: HashMap<i64, Span> = HashMap::new();
+        for (arm, (expressions, _)) in arms.iter().enumerate() {
+            for label in expressions {
+                match self.evaluate(label) {
+                    Ok(ProgramTypes::Integer(Some(value))) => match first.get(&value) {
+                        Some(span) => self.errors.push(SemanticError::DuplicateLabel(value, label.span, *span)),
+                        None => {
+                            first.insert(value, label.span);
+                            labels.push((value, arm));
+                        }
+                    },
+                    Ok(t) => self.errors.push(SemanticError::CaseType(t, label.span)),
+                    Err(e) => self.errors.push(e)
+                }
+            }
+        }
+        labels
+    }
+
+    /// Переход по значению rax к ветви `case`. Плотные метки выбираются через
+    /// таблицу смещений в `.text`, остальные - цепочкой сравнений
+    fn case_dispatch(&mut self, labels: &[(i64, usize)]) -> Dispatch {
+        let (min, max) = match (labels.iter().map(|l| l.0).min(), labels.iter().map(|l| l.0).max()) {
+            (Some(min), Some(max)) => (min, max),
+            _ => return Dispatch { jumps: vec![(self.jpm_default(), None)], table: None }
+        };
+        let range = max as i128 - min as i128 + 1;
+        if labels.len() < 4 || range > 2 * labels.len() as i128 || i32::try_from(min).is_err() {
+            let mut jumps = Vec::new();
+            for (value, arm) in labels {
+                match i32::try_from(*value) {
+                    // cmp rax, imm32
+                    Ok(value) => {
+                        self.asm.append(&mut vec![0x48, 0x3d]);
+                        self.asm.append(&mut value.to_le_bytes().to_vec());
+                    },
+                    // mov rbx, imm64; cmp rax, rbx
+                    Err(_) => {
+                        self.asm.append(&mut vec![0x48, 0xbb]);
+                        self.asm.append(&mut value.to_le_bytes().to_vec());
+                        self.asm.append(&mut vec![0x48, 0x39, 0xd8]);
+                    }
+                }
+                // je rel32
+                self.asm.append(&mut vec![0x0f, 0x84, 0x00, 0x00, 0x00, 0x00]);
+                self.cur_pos();
+                jumps.push((self.position - 4, Some(*arm)));
+            }
+            jumps.push((self.jpm_default(), None));
+            return Dispatch { jumps, table: None };
+        }
+
+        // sub rax, min; cmp rax, range; jae rel32
+        self.asm.append(&mut vec![0x48, 0x2d]);
+        self.asm.append(&mut (min as i32).to_le_bytes().to_vec());
+        self.asm.append(&mut vec![0x48, 0x3d]);
+        self.asm.append(&mut (range as i32).to_le_bytes().to_vec());
+        self.asm.append(&mut vec![0x0f, 0x83, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        let default = self.position - 4;
+        // lea rbx, [rip + таблица]; movsxd rax, [rbx + rax*4]; add rax, rbx; jmp rax
+        self.asm.append(&mut vec![0x48, 0x8d, 0x1d, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        let lea = self.position - 4;
+        self.asm.append(&mut vec![0x48, 0x63, 0x04, 0x83, 0x48, 0x01, 0xd8, 0xff, 0xe0]);
+        self.cur_pos();
+        let table_start = self.position;
+        self.patch_rel32(lea, table_start);
+
+        let mut entries = vec![None; range as usize];
+        for (value, arm) in labels {
+            entries[(*value - min) as usize] = Some(*arm);
+        }
+        self.asm.resize(self.asm.len() + 4 * range as usize, 0);
+        self.cur_pos();
+        Dispatch { jumps: vec![(default, None)], table: Some((table_start, entries)) }
+    }
+
+    /// Тело цикла с обратным переходом к проверке условия
+    fn test_loop_body(&mut self, operator: Operator, start: u64) {
+        self.loops.push(Loop { start, breaks: Vec::new(), exits: Vec::new(), continues: Vec::new(), returns: false });
+        let res = self.test_operator(operator);
+        self.record(res);
+        self.jmp_cycle(start);
+    }
+
+    /// Направляет все `break` текущего цикла на первую инструкцию после него.
+    /// После цикла переменная имеет значение, если получила его до цикла или
+    /// перед каждым `break` цикла без выхода по условию. Присваивания в конце
+    /// тела и перед `continue` доходят до чтений в цикле, начиная с `loads`
+    fn leave_loop(&mut self, before: State, loads: usize, span: Option<Span>, endless: bool) {
+        let current = self.loops.pop().unwrap();
+        let end = self.state();
+        for state in current.continues.iter().chain([&end]) {
+            self.backedge(state, loads);
+        }
+        // отброшенный при `-O1` `break` тоже выход из цикла
+        let exited = !current.exits.is_empty() || current.returns;
+        for position in current.breaks {
+            self.jmp(position);
+        }
+        let mut exits = Vec::new();
+        match span {
+            // выход по условию возможен до первой итерации, после конца
+            // тела и после `continue`
+            Some(span) if !endless => {
+                exits.push((before, Path::Loop(span)));
+                exits.push((self.state(), Path::Loop(span)));
+                exits.extend(current.continues.into_iter().map(|state| (state, Path::Loop(span))));
+            },
+            _ => ()
+        }
+        exits.extend(current.exits);
+        self.set_state(Self::merge(exits));
+        self.terminator = None;
+        match span {
+            Some(span) if endless && !exited =>
+                self.warn(Warning::InfiniteLoop(span)),
+            _ => ()
+        }
+    }
+
+    /// Предупреждает о всегда ложном условии и сообщает, что условие всегда истинно
+    fn constant_condition(&mut self, expression: &Expression) -> bool {
+        match self.condition_value(expression) {
+            Some(false) => {
+                self.warn(Warning::ConstantCondition(expression.span));
+                false
+            },
+            value => value == Some(true)
+        }
+    }
+
+    /// Оператор после `break`, `continue` или `return` не выполняется
+    fn check_reachable(&mut self) {
+        if !self.reachable {
+            if let Some(span) = self.terminator.take() {
+                self.warn(Warning::UnreachableCode(span));
             }
         }
     }
 
+    fn mark(&mut self, id: u64, update: impl FnOnce(&mut Usage)) {
+        if let Some(declaration) = self.variables.get(&id) {
+            update(self.usage.entry(*declaration).or_default());
+        }
+    }
+
+    /// Предупреждения о переменных, значение которых не читается, и о каждом
+    /// присваивании, значение которого затирается или не читается вовсе
+    fn unused(&mut self) {
+        let mut warnings = Vec::new();
+        for (id, span) in std::mem::take(&mut self.declared) {
+            let warning = match self.usage.get(&span).copied().unwrap_or_default() {
+                Usage { read: true, .. } | Usage { assigned: true, .. } => continue,
+                Usage { input: Some(at), .. } => Warning::UnusedInput(id, span, at),
+                Usage { .. } => Warning::UnusedVariable(id, span)
+            };
+            warnings.push((span, warning));
+        }
+        for (id, at) in self.dead_stores() {
+            warnings.push((at, Warning::UnusedAssignment(id, at)));
+        }
+        // в порядке следования в тексте программы
+        warnings.sort_by_key(|(span, _)| span.start);
+        for (_, warning) in warnings {
+            self.warn(warning);
+        }
+    }
+
+    /// Программа с синтаксическими ошибками разобрана не полностью,
+    /// поэтому предупреждения для неё не выдаются
+    fn warn(&mut self, warning: Warning) {
+        if !self.partial {
+            self.warnings.push(warning);
+        }
+    }
+
+    fn test_condition(&mut self, expression: Expression) -> SemanticResult<()> {
+        let span = expression.span;
+        match self.test_expression(expression)? {
+            ProgramTypes::Boolean(_) => Ok(()),
+            t => Err(SemanticError::NotBoolean(t, span))
+        }
+    }
+
+    fn initialize(&mut self, id: u64, t: &ProgramTypes) -> ProgramTypes {
+        let i_type = match t {
+            ProgramTypes::Boolean(_) => ProgramTypes::Boolean(Some(false)),
+            ProgramTypes::Float(_) => ProgramTypes::Float(Some(0.)),
+            ProgramTypes::Integer(_) => ProgramTypes::Integer(Some(0)),
+            // элементы массивов обнуляются при объявлении
+            ProgramTypes::Array(..) => t.clone()
+        };
+        self.identifiers.insert(id, i_type.clone());
+        i_type
+    }
+
+    fn record(&mut self, res: SemanticResult<()>) {
+        if let Err(e) = res {
+            self.errors.push(e);
+        }
+    }
+
     fn test_expression(&mut self, expression: Expression) -> SemanticResult<ProgramTypes> {
-        let Expression {operands, operations} = expression;
+        let expression = if self.optimize { self.fold(expression) } else { expression };
+        let Expression { operands, operations, .. } = expression;
         let (mut operands, operations) = (operands.into_iter(), operations.into_iter());
-        let op1 = self.test_operand(operands.next().unwrap())?;
+        let first = operands.next().unwrap();
+        let mut left_span = first.span;
+        let op1 = self.test_operand(first)?;
         let mut current_type = op1;
 
         for (operand, operation) in operands.zip(operations) {
+            let span = operand.span;
             self.push_rax();
             let op = self.test_operand(operand)?;
 
-            match op {
-                ProgramTypes::Boolean(_) => match operation {
+            if let ProgramTypes::Boolean(_) = op {
+                match operation {
                     RelationOperations::Equal|RelationOperations::NotEqual => (),
-                    o => return Err(SemanticError::InvalidOperation(op, o.into()))
-                },
-                _ => ()
+                    o => return Err(SemanticError::InvalidOperation(op, o.into(), span))
+                }
             }
 
             self.pop_rbx();
-            
-            if !(current_type.clone() & op.clone()) {
-                return Err(SemanticError::TypeError(op, current_type));
-            }
 
+            let common = match self.widen(&current_type, &op) {
+                Some(t) => t,
+                None => return Err(SemanticError::TypeError(op, current_type, span, left_span))
+            };
+            left_span = left_span.to(span);
+
+            let float = matches!(common, ProgramTypes::Float(_));
             current_type = ProgramTypes::Boolean(None);
 
-            match current_type {
-                ProgramTypes::Float(_) => {
-                    self.init_fpu();
-                    self.fcomi();
-                },
-                _ => self.cmp()
-            }
-            match operation {
-                RelationOperations::Equal => self.eq(),
-                RelationOperations::NotEqual => self.neq(),
-                RelationOperations::Greater => self.more(),
-                RelationOperations::GreaterEqual => self.more_eq(),
-                RelationOperations::Less => self.less(),
-                RelationOperations::LessEqual => self.less_eq()
+            if float {
+                // fucomi сравнивает правый операнд с левым и выставляет
+                // флаги как при беззнаковом сравнении, а с NaN - ещё и PF:
+                // тогда по IEEE истинно только `!=`
+                self.fucomi();
+                self.unordered(matches!(operation, RelationOperations::NotEqual));
+                match operation {
+                    RelationOperations::Equal => self.eq(),
+                    RelationOperations::NotEqual => self.neq(),
+                    RelationOperations::Greater => self.below(),
+                    RelationOperations::GreaterEqual => self.below_eq(),
+                    RelationOperations::Less => self.above(),
+                    RelationOperations::LessEqual => self.above_eq()
+                }
+            } else {
+                self.cmp();
+                match operation {
+                    RelationOperations::Equal => self.eq(),
+                    RelationOperations::NotEqual => self.neq(),
+                    RelationOperations::Greater => self.more(),
+                    RelationOperations::GreaterEqual => self.more_eq(),
+                    RelationOperations::Less => self.less(),
+                    RelationOperations::LessEqual => self.less_eq()
+                }
             }
         }
 
@@ -250,32 +1129,59 @@ impl Semantic {
     }
 
     fn test_operand(&mut self, operand: Operand) -> SemanticResult<ProgramTypes> {
-        let Operand { terms, operations } = operand;
+        let Operand { terms, operations, .. } = operand;
         let (mut terms, operations) = (terms.into_iter(), operations.into_iter());
-        let op1 = self.test_term(terms.next().unwrap())?;
+        let first = terms.next().unwrap();
+        let mut left_span = first.span;
+        let mut left = Left { start: self.position, value: fold::term_boolean(&first), pure: fold::pure_term(&first) };
+        let mut current_type = self.test_term(first)?;
 
         for (term, operation) in terms.zip(operations) {
-            self.push_rax();
+            let span = term.span;
+            let right = (fold::term_boolean(&term), fold::pure_term(&term));
+            let shortcut = match operation {
+                AdditionOperations::Or if self.optimize => left.shortcut(true, right.0),
+                _ => None
+            };
+            if let Some(shortcut) = shortcut {
+                let op = self.simplify(shortcut, &mut left, right, |semantic| semantic.test_term(term))?;
+                current_type = fold::logical(&current_type, op, operation.to_string(), span, left_span)?;
+                left_span = left_span.to(span);
+                continue;
+            }
+            left.value = None;
+            left.pure &= right.1;
+            // при истинном левом операнде `||` правый не вычисляется
+            let skip = match operation {
+                AdditionOperations::Or => Some(self.jnz_default()),
+                _ => {
+                    self.push_rax();
+                    None
+                }
+            };
             let op = self.test_term(term)?;
 
             match op {
                 ProgramTypes::Boolean(_) => match operation {
                     AdditionOperations::Or => (),
-                    o => return Err(SemanticError::InvalidOperation(op, o.to_string())),
+                    o => return Err(SemanticError::InvalidOperation(op, o.to_string(), span)),
                 },
-                _ => match operation {
-                    AdditionOperations::Or => return Err(SemanticError::InvalidOperation(op, AdditionOperations::Or.to_string())),
-                    _ => ()
+                _ => if let AdditionOperations::Or = operation {
+                    return Err(SemanticError::InvalidOperation(op, AdditionOperations::Or.to_string(), span));
                 }
             }
 
-            self.pop_rbx();
-            
-            if !(op.clone() & op1.clone()) {
-                return Err(SemanticError::TypeError(op, op1));
+            if skip.is_none() {
+                self.pop_rbx();
             }
 
-            match op {
+            current_type = match self.widen(&current_type, &op) {
+                Some(t) => t,
+                None => return Err(SemanticError::TypeError(op, current_type, span, left_span))
+            };
+            left_span = left_span.to(span);
+
+            match current_type {
                 ProgramTypes::Float(_) => {
                     self.init_fpu();
                     match operation {
@@ -285,43 +1191,81 @@ impl Semantic {
                     self.save_fpu_rax();
                 },
                 _ => match operation {
-                    AdditionOperations::Or => self.or(),
+                    AdditionOperations::Or => (),
                     AdditionOperations::Addition => self.add_i64(),
                     AdditionOperations::Subtraction => self.sub_i64()
                 }
             }
+            if let Some(skip) = skip {
+                self.jz(skip);
+            }
         }
 
-        Ok(op1)
+        Ok(current_type)
     }
 
     fn test_term(&mut self, term: Term) -> SemanticResult<ProgramTypes> {
-        let Term { multipliers, operations } = term;
+        let Term { multipliers, operations, .. } = term;
         let (mut multipliers, operations) = (multipliers.into_iter(), operations.into_iter());
-        let op1  = self.test_multiplier(multipliers.next().unwrap())?;
+        let first = multipliers.next().unwrap();
+        let mut left_span = first.span();
+        let mut left = Left { start: self.position, value: fold::boolean(&first), pure: fold::pure(&first) };
+        let mut current_type = self.test_multiplier(first)?;
 
         for (multiplier, operation) in multipliers.zip(operations) {
-            self.push_rax();
+            let span = multiplier.span();
+            let right = (fold::boolean(&multiplier), fold::pure(&multiplier));
+            let shortcut = match operation {
+                MultiplicationOperations::And if self.optimize => left.shortcut(false, right.0),
+                _ => None
+            };
+            if let Some(shortcut) = shortcut {
+                let op = self.simplify(shortcut, &mut left, right, |semantic| semantic.test_multiplier(multiplier))?;
+                current_type = fold::logical(&current_type, op, operation.to_string(), span, left_span)?;
+                left_span = left_span.to(span);
+                continue;
+            }
+            left.value = None;
+            left.pure &= right.1 && fold::pure_operation(&operation);
+            // при ложном левом операнде `&&` правый не вычисляется
+            let skip = match operation {
+                MultiplicationOperations::And => Some(self.jz_default()),
+                _ => {
+                    self.push_rax();
+                    None
+                }
+            };
             let op = self.test_multiplier(multiplier)?;
 
             match op {
                 ProgramTypes::Boolean(_) => match operation {
                     MultiplicationOperations::And => (),
-                    o => return Err(SemanticError::InvalidOperation(op, o.to_string())),
+                    o => return Err(SemanticError::InvalidOperation(op, o.to_string(), span)),
                 },
-                _ => match operation {
-                    MultiplicationOperations::And => return Err(SemanticError::InvalidOperation(op, MultiplicationOperations::And.to_string())),
-                    _ => ()
+                _ => if let MultiplicationOperations::And = operation {
+                    return Err(SemanticError::InvalidOperation(op, MultiplicationOperations::And.to_string(), span));
                 }
             }
 
-            self.pop_rbx();
-            
-            if !(op.clone() & op1.clone()) {
-                return Err(SemanticError::TypeError(op, op1));
+            if let MultiplicationOperations::Remainder|MultiplicationOperations::IntegerDivision = operation {
+                for (t, span) in [(&current_type, left_span), (&op, span)] {
+                    if !matches!(t, ProgramTypes::Integer(_)) {
+                        return Err(SemanticError::InvalidOperation(t.clone(), operation.to_string(), span));
+                    }
+                }
             }
 
-            match op {
+            if skip.is_none() {
+                self.pop_rbx();
+            }
+
+            current_type = match self.widen(&current_type, &op) {
+                Some(t) => t,
+                None => return Err(SemanticError::TypeError(op, current_type, span, left_span))
+            };
+            left_span = left_span.to(span);
+
+            match current_type {
                 ProgramTypes::Float(_) => {
                     self.init_fpu();
                     match operation {
@@ -331,30 +1275,51 @@ impl Semantic {
                     self.save_fpu_rax();
                 },
                 _ => match operation {
-                    MultiplicationOperations::And => self.and(),
+                    MultiplicationOperations::And => (),
                     MultiplicationOperations::Multiplication => self.mul_i64(),
-                    MultiplicationOperations::Division => self.div_i64()
+                    MultiplicationOperations::Division|MultiplicationOperations::IntegerDivision => {
+                        self.check_divisor(span.line);
+                        self.div_i64()
+                    },
+                    MultiplicationOperations::Remainder => {
+                        self.check_divisor(span.line);
+                        self.rem_i64()
+                    }
                 }
             }
+            if let Some(skip) = skip {
+                self.jz(skip);
+            }
         }
 
-        Ok(op1)
+        Ok(current_type)
     }
 
     fn test_multiplier(&mut self, multiplier: Multiplier) -> SemanticResult<ProgramTypes> {
         match multiplier  {
-            Multiplier::Identifier(id) => {
-                self.mov_rax_ident(id);
-                match self.identifiers.get(&id) {
-                    Some(v) => self.test_ident(id, v.clone()),
-                    None => Err(SemanticError::NotDefined(id))
+            Multiplier::Identifier(ident) if self.constants.contains_key(&ident.id) => {
+                let value = self.constants[&ident.id].clone();
+                self.immediate(&value);
+                Ok(value)
+            },
+            Multiplier::Constant(value, _) => {
+                self.immediate(&value);
+                Ok(value)
+            },
+            Multiplier::Identifier(ident) => {
+                self.read(ident.id);
+                self.mov_rax_ident(ident.id);
+                match self.identifiers.get(&ident.id) {
+                    Some(ProgramTypes::Array(..)) => Err(self.shadowing(ident.id, SemanticError::ArrayUsage(ident.id, ident.span))),
+                    Some(v) => self.test_ident(ident.id, v.clone(), ident.span),
+                    None => Err(SemanticError::NotDefined(ident.id, ident.span))
                 }
             },
-            Multiplier::Boolean(b) => {
+            Multiplier::Boolean(b, _) => {
                 self.asm_bool(b);
                 Ok(ProgramTypes::Boolean(None))
             },
-            Multiplier::Variable(id) => {
+            Multiplier::Variable(id, _) => {
                 let var = self.vars.get(&id).unwrap().clone();
                 match var {
                     LexerDigitalData::Float(v) => {
@@ -368,25 +1333,150 @@ impl Semantic {
                 }
             }
             Multiplier::Expression(e) => self.test_expression(e),
-            Multiplier::Not(m) => {
-                let res = self.test_multiplier(*m);
-                if let Ok(res) = res {
-                    match res {
-                        ProgramTypes::Boolean(_) => (),
-                        t => return Err(SemanticError::InvalidOperation(t, "унарная".into()))
-                    }
+            // `!!x` при `-O1` - это `x`
+            Multiplier::Not(m, _) if self.optimize && matches!(*m, Multiplier::Not(..)) => {
+                let Multiplier::Not(m, span) = *m else { unreachable!() };
+                match self.test_multiplier(*m)? {
+                    ProgramTypes::Boolean(_) => Ok(ProgramTypes::Boolean(None)),
+                    t => Err(SemanticError::InvalidOperation(t, Message::Unary.to_string(), span))
+                }
+            },
+            Multiplier::Not(m, span) => {
+                match self.test_multiplier(*m)? {
+                    ProgramTypes::Boolean(_) => (),
+                    t => return Err(SemanticError::InvalidOperation(t, Message::Unary.to_string(), span))
                 }
                 self.not_rax();
                 Ok(ProgramTypes::Boolean(None))
+            },
+            // отрицательные литералы вычисляются при компиляции
+            Multiplier::Neg(m, _) if matches!(*m, Multiplier::Variable(..)) => {
+                let Multiplier::Variable(id, _) = *m else { unreachable!() };
+                match self.vars.get(&id).unwrap().clone() {
+                    LexerDigitalData::Float(v) => {
+                        self.mov_rax_f64(-v);
+                        Ok(ProgramTypes::Float(None))
+                    },
+                    LexerDigitalData::Integer(v) => {
+                        self.mov_rax_i64(v.wrapping_neg());
+                        Ok(ProgramTypes::Integer(None))
+                    }
+                }
+            },
+            Multiplier::Convert(target, e, span) => match (self.test_expression(e)?, target) {
+                (ProgramTypes::Integer(_), ProgramTypes::Float(_)) => {
+                    self.int_to_float_rax();
+                    Ok(ProgramTypes::Float(None))
+                },
+                (ProgramTypes::Float(_), ProgramTypes::Integer(_)) => {
+                    self.float_to_int_rax();
+                    Ok(ProgramTypes::Integer(None))
+                },
+                (ProgramTypes::Boolean(_), target) =>
+                    Err(SemanticError::InvalidOperation(ProgramTypes::Boolean(None), Message::Conversion(target.to_string()).to_string(), span)),
+                (_, target) => Ok(target)
+            },
+            Multiplier::Index(ident, index, span) => {
+                self.read(ident.id);
+                let t = self.test_element(ident, *index, span)?;
+                // mov rax, [rbx]
+                self.asm.append(&mut vec![0x48, 0x8b, 0x03]);
+                self.cur_pos();
+                Ok(t)
+            },
+            Multiplier::Power(base, exponent, span) => {
+                let base_span = base.span();
+                let base = self.test_multiplier(*base)?;
+                self.push_rax();
+                let exponent_span = exponent.span();
+                let exponent = self.test_multiplier(*exponent)?;
+                self.pop_rbx();
+                for (t, span) in [(&base, base_span), (&exponent, exponent_span)] {
+                    if let ProgramTypes::Boolean(_) = t {
+                        return Err(SemanticError::InvalidOperation(t.clone(), Message::Power.to_string(), span));
+                    }
+                }
+                match self.widen(&base, &exponent) {
+                    Some(ProgramTypes::Float(_)) => {
+                        self.pow_f64();
+                        Ok(ProgramTypes::Float(None))
+                    },
+                    _ => {
+                        // mov edx, imm32: строка для сообщения о делении на ноль
+                        self.asm.push(0xba);
+                        self.asm.append(&mut span.line.to_le_bytes().to_vec());
+                        self.asm.append(&mut vec![0xe8, 0x00, 0x00, 0x00, 0x00]);
+                        self.cur_pos();
+                        self.powers.push(self.position - 4);
+                        Ok(ProgramTypes::Integer(None))
+                    }
+                }
+            },
+            Multiplier::Call(ident, arguments, span) => match self.test_call(ident, arguments, span)? {
+                Some(result) => Ok(result),
+                None => Err(SemanticError::NoResult(ident.id, span))
+            },
+            Multiplier::Neg(m, span) => match self.test_multiplier(*m)? {
+                ProgramTypes::Integer(_) => {
+                    self.neg_rax();
+                    Ok(ProgramTypes::Integer(None))
+                },
+                ProgramTypes::Float(_) => {
+                    self.neg_f64_rax();
+                    Ok(ProgramTypes::Float(None))
+                },
+                t => Err(SemanticError::InvalidOperation(t, Message::UnaryMinus.to_string(), span))
             }
         }
     }
 
-    fn test_ident(&mut self, id: u64, t: ProgramTypes) -> SemanticResult<ProgramTypes> {
+    /// Адрес элемента массива в rbx после проверки индекса,
+    /// возвращает тип элемента
+    fn test_element(&mut self, ident: Ident, index: Expression, span: Span) -> SemanticResult<ProgramTypes> {
+        let (element, len) = match self.identifiers.get(&ident.id) {
+            Some(ProgramTypes::Array(element, len)) => (*element.clone(), *len),
+            None if self.constants.contains_key(&ident.id) => return Err(SemanticError::NotArray(ident.id, ident.span)),
+            Some(_) => return Err(self.shadowing(ident.id, SemanticError::NotArray(ident.id, ident.span))),
+            None => return Err(SemanticError::NotDefined(ident.id, ident.span))
+        };
+        let index_span = index.span;
+        match self.test_expression(index)? {
+            ProgramTypes::Integer(_) => (),
+            t => return Err(SemanticError::IndexType(t, index_span))
+        }
+        self.check_bounds(len, span.line);
+        self.lea_element(ident.id);
+        Ok(element)
+    }
+
+    /// Общий тип операндов в rax (левый) и rbx (правый): целый операнд
+    /// в паре с вещественным расширяется до вещественного
+    fn widen(&mut self, left: &ProgramTypes, right: &ProgramTypes) -> Option<ProgramTypes> {
+        match (left, right) {
+            (ProgramTypes::Integer(_), ProgramTypes::Float(_)) => {
+                self.int_to_float_rax();
+                Some(ProgramTypes::Float(None))
+            },
+            (ProgramTypes::Float(_), ProgramTypes::Integer(_)) => {
+                self.int_to_float_rbx();
+                Some(ProgramTypes::Float(None))
+            },
+            (left, right) if left.clone() & right.clone() => Some(left.clone()),
+            _ => None
+        }
+    }
+
+    fn test_ident(&mut self, id: u64, t: ProgramTypes, span: Span) -> SemanticResult<ProgramTypes> {
+        if self.partial {
+            return Ok(t);
+        }
         match t {
-            ProgramTypes::Boolean(None) => Err(SemanticError::NotDefined(id)),
-            ProgramTypes::Float(None) => Err(SemanticError::NotDefined(id)),
-            ProgramTypes::Integer(None) => Err(SemanticError::NotDefined(id)),
+            ProgramTypes::Boolean(None)|
+            ProgramTypes::Float(None)|
+            ProgramTypes::Integer(None) => Err(self.shadowing(id, match self.paths.get(&id) {
+                Some(path) => SemanticError::MaybeUninitialized(id, span, *path),
+                None => SemanticError::NotDefined(id, span)
+            })),
             _ => Ok(t)
         }
     }
@@ -404,9 +1494,19 @@ impl Semantic {
             ProgramTypes::Float(_) => self.asm_idents.push((self.reserve+2, self.position - 8, true)),
             _ => self.asm_idents.push((self.reserve+1, self.position - 8, true))
         }
-        self.asm.append(&mut vec![0x48, 0xbe, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
-        self.cur_pos();
-        self.asm_idents.push((id, self.position - 8, true));
+        match self.locals.get(&id).copied() {
+            // lea rsi, [rbp+disp32]
+            Some(offset) => {
+                self.asm.append(&mut vec![0x48, 0x8d, 0xb5]);
+                self.asm.append(&mut offset.to_le_bytes().to_vec());
+                self.cur_pos();
+            },
+            None => {
+                self.asm.append(&mut vec![0x48, 0xbe, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+                self.cur_pos();
+                self.asm_idents.push((id, self.position - 8, true));
+            }
+        }
         self.asm.append(&mut vec![0xe8, 0x00, 0x00, 0x00, 0x00]);
         self.cur_pos();
         self.asm_idents.push((self.reserve+5, self.position - 4, false));
@@ -420,7 +1520,187 @@ impl Semantic {
         }
     }
 
+    /// Ввод элемента массива по адресу из rbx, который scanf сохраняет
+    fn input_element(&mut self, i_type: ProgramTypes) {
+        self.asm.append(&mut vec![0x48, 0xbf, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        match i_type {
+            ProgramTypes::Float(_) => self.asm_idents.push((self.reserve+2, self.position - 8, true)),
+            _ => self.asm_idents.push((self.reserve+1, self.position - 8, true))
+        }
+        // mov rsi, rbx; call scanf
+        self.asm.append(&mut vec![0x48, 0x89, 0xde, 0xe8, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.asm_idents.push((self.reserve+5, self.position - 4, false));
+        if i_type & ProgramTypes::Boolean(None) {
+            self.asm.append(&mut vec![0x48, 0x8b, 0x03, 0x48, 0x85, 0xc0, 0x74, 12]);
+            self.asm_bool(true);
+            self.asm.append(&mut vec![0xeb, 10]);
+            self.asm_bool(false);
+            self.mov_element_rax();
+        }
+    }
+
+    /// Индекс в rax сравнивается с длиной массива без знака, поэтому
+    /// отрицательный индекс тоже считается выходом за границы.
+    /// При ошибке: rcx = индекс, rdx = строка, r8 = длина
+    fn check_bounds(&mut self, len: u64, line: u32) {
+        // cmp rax, imm32; jb +0x13. Длина не больше `MAX_ARRAY_LEN`,
+        // поэтому знаковое расширение imm32 её не меняет
+        self.asm.append(&mut vec![0x48, 0x3d]);
+        self.asm.append(&mut (len as u32).to_le_bytes().to_vec());
+        self.asm.append(&mut vec![0x72, 0x13]);
+        // mov rcx, rax; mov edx, imm32; mov r8d, imm32; call rel32
+        self.asm.append(&mut vec![0x48, 0x89, 0xc1, 0xba]);
+        self.asm.append(&mut line.to_le_bytes().to_vec());
+        self.asm.append(&mut vec![0x41, 0xb8]);
+        self.asm.append(&mut (len as u32).to_le_bytes().to_vec());
+        self.asm.append(&mut vec![0xe8, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.bounds.push(self.position - 4);
+    }
+
+    /// Подпрограмма сообщает о выходе за границы массива
+    fn bounds_routine(&mut self) {
+        self.bounds_checked = true;
+        let target = self.position;
+        for call_position in std::mem::take(&mut self.bounds) {
+            self.patch_rel32(call_position, target);
+        }
+        self.runtime_error(self.reserve+8);
+    }
+
+    /// Делитель в rbx, делимое в rax. Подпрограмма вызывается, только
+    /// если делитель 0 или -1, то есть rbx + 1 <= 1 без знака.
+    /// При ошибке: rdx = строка
+    fn check_divisor(&mut self, line: u32) {
+        // lea rcx, [rbx+1]; cmp rcx, 1; ja +10; mov edx, imm32; call rel32
+        self.asm.append(&mut vec![0x48, 0x8d, 0x4b, 0x01, 0x48, 0x83, 0xf9, 0x01, 0x77, 0x0a, 0xba]);
+        self.asm.append(&mut line.to_le_bytes().to_vec());
+        self.asm.append(&mut vec![0xe8, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.divisions.push(self.position - 4);
+    }
+
+    /// Подпрограмма сообщает о делении на ноль и о переполнении
+    /// i64::MIN / -1, на которых `idiv` завершил бы программу сигналом.
+    /// Возвращает адрес сообщения, на него переходит и возведение в степень
+    fn division_routine(&mut self) -> u64 {
+        self.division_checked = true;
+        let target = self.position;
+        for call_position in std::mem::take(&mut self.divisions) {
+            self.patch_rel32(call_position, target);
+        }
+        self.asm.append(&mut vec![
+            0x48, 0x85, 0xdb,                   // test rbx, rbx
+            0x74, 0x09,                         // jz .fail
+            0x48, 0x89, 0xc1,                   // mov rcx, rax
+            0x48, 0xf7, 0xd9,                   // neg rcx
+            0x70, 0x01,                         // jo .fail
+            0xc3                                // ret
+        ]);
+        self.cur_pos();
+        let failure = self.position;
+        self.runtime_error(self.reserve+12);
+        failure
+    }
+
+    /// Печатает сообщение по формату `format` в stderr и завершает программу
+    /// с кодом ошибки выполнения, как и интерпретатор. Стек выравнивается
+    /// заново, так как подпрограммы вызываются из середины вычисления
+    /// выражения, а `exit` из libc сбрасывает уже выведенное в stdout
+    fn runtime_error(&mut self, format: u64) {
+        // and rsp, -16; mov edi, 2; mov rsi, imm64
+        self.asm.append(&mut vec![0x48, 0x83, 0xe4, 0xf0, 0xbf, 0x02, 0x00, 0x00, 0x00,
+            0x48, 0xbe, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.asm_idents.push((format, self.position - 8, true));
+        // xor eax, eax; call dprintf
+        self.asm.append(&mut vec![0x31, 0xc0, 0xe8, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.asm_idents.push((self.reserve+9, self.position - 4, false));
+        // mov edi, imm32; call exit
+        self.asm.push(0xbf);
+        self.asm.append(&mut Phase::Runtime.exit_code().to_le_bytes().to_vec());
+        self.asm.append(&mut vec![0xe8, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.asm_idents.push((self.reserve+7, self.position - 4, false));
+    }
+
+    /// Записывает смещение rel32 от конца поля до цели
+    fn patch_rel32(&mut self, call_position: u64, target: u64) {
+        let offset = (target as i64 - call_position as i64 - 4) as i32;
+        self.asm[call_position as usize..call_position as usize + 4].copy_from_slice(&offset.to_le_bytes());
+    }
+
+    /// Целая степень rax ** rbx возведением в квадрат, результат в rax.
+    /// При отрицательном показателе основание заменяется на 1 / rax,
+    /// поэтому результат совпадает с делением 1 на степень. Нулевое
+    /// основание уходит в сообщение `failure` о делении на ноль
+    fn power_routine(&mut self, failure: u64) {
+        let target = self.position;
+        for call_position in std::mem::take(&mut self.powers) {
+            self.patch_rel32(call_position, target);
+        }
+        self.asm.append(&mut vec![
+            0x48, 0x85, 0xdb,                   // test rbx, rbx
+            0x79, 0x19,                         // jns .positive
+            0x48, 0x85, 0xc0,                   // test rax, rax
+            0x0f, 0x84, 0x00, 0x00, 0x00, 0x00  // jz failure
+        ]);
+        self.cur_pos();
+        self.patch_rel32(self.position - 4, failure);
+        self.asm.append(&mut vec![
+            0x48, 0x89, 0xc1,                   // mov rcx, rax
+            0xb8, 0x01, 0x00, 0x00, 0x00,       // mov eax, 1
+            0x48, 0x99, 0x48, 0xf7, 0xf9,       // cqo; idiv rcx
+            0x48, 0xf7, 0xdb,                   // neg rbx
+            // .positive:
+            0x48, 0x89, 0xc1,                   // mov rcx, rax
+            0xb8, 0x01, 0x00, 0x00, 0x00,       // mov eax, 1
+            // .loop:
+            0x48, 0x85, 0xdb,                   // test rbx, rbx
+            0x74, 0x12,                         // jz .done
+            0xf6, 0xc3, 0x01,                   // test bl, 1
+            0x74, 0x04,                         // jz .square
+            0x48, 0x0f, 0xaf, 0xc1,             // imul rax, rcx
+            // .square:
+            0x48, 0x0f, 0xaf, 0xc9,             // imul rcx, rcx
+            0x48, 0xd1, 0xeb,                   // shr rbx, 1
+            0xeb, 0xe9,                         // jmp .loop
+            // .done:
+            0xc3                                // ret
+        ]);
+        self.cur_pos();
+    }
+
+    /// lea rbx, [rax*8+массив] или lea rbx, [rbp+rax*8+disp32] для локального
+    fn lea_element(&mut self, id: u64) {
+        if let Some(offset) = self.locals.get(&id).copied() {
+            self.asm.append(&mut vec![0x48, 0x8d, 0x9c, 0xc5]);
+            self.asm.append(&mut offset.to_le_bytes().to_vec());
+            self.cur_pos();
+            return;
+        }
+        self.asm.append(&mut vec![0x48, 0x8d, 0x1c, 0xc5, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.asm_idents.push((id, self.position - 4, false));
+    }
+
+    /// mov [rbx], rax
+    fn mov_element_rax(&mut self) {
+        self.asm.append(&mut vec![0x48, 0x89, 0x03]);
+        self.cur_pos();
+    }
+
     fn assign(&mut self, id: u64) {
+        if let Some(offset) = self.locals.get(&id).copied() {
+            // mov [rbp+disp32], rax
+            self.asm.append(&mut vec![0x48, 0x89, 0x85]);
+            self.asm.append(&mut offset.to_le_bytes().to_vec());
+            self.cur_pos();
+            return;
+        }
         self.asm.append(&mut vec![0x48, 0x89, 0x04, 0x25, 0x00, 0x00, 0x00, 0x00]);
         self.cur_pos();
         self.asm_idents.push((id, self.position - 4, false));
@@ -451,6 +1731,20 @@ impl Semantic {
         self.asm_idents.push((self.reserve+6, self.position - 4, false));
     }
 
+    /// printf("%s", строка): формат и строки лежат в `.rodata`,
+    /// строка с номером k адресуется как reserve+13+k
+    fn print_text(&mut self, id: u64) {
+        self.asm.append(&mut vec![0x48, 0xbf, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.asm_idents.push((self.reserve+10, self.position - 8, true));
+        self.asm.append(&mut vec![0x48, 0xbe, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.asm_idents.push((self.reserve+13+id, self.position - 8, true));
+        self.asm.append(&mut vec![0x48, 0x31, 0xc0, 0xe8, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.asm_idents.push((self.reserve+6, self.position - 4, false));
+    }
+
     fn mov_rax_f64(&mut self, num: f64) {
         self.asm.append(&mut vec![0x48, 0xb8]);
         self.asm.append(&mut num.to_le_bytes().to_vec());
@@ -468,11 +1762,85 @@ impl Semantic {
         self.cur_pos();
     }
 
+    fn neg_rax(&mut self) {
+        self.asm.append(&mut vec![0x48, 0xf7, 0xd8]);
+        self.cur_pos();
+    }
+
+    /// Меняет знак вещественного числа в rax: btc rax, 63
+    fn neg_f64_rax(&mut self) {
+        self.asm.append(&mut vec![0x48, 0x0f, 0xba, 0xf8, 0x3f]);
+        self.cur_pos();
+    }
+
+    /// cvtsi2sd xmm0, rax; movq rax, xmm0
+    fn int_to_float_rax(&mut self) {
+        self.asm.append(&mut vec![0xf2, 0x48, 0x0f, 0x2a, 0xc0, 0x66, 0x48, 0x0f, 0x7e, 0xc0]);
+        self.cur_pos();
+    }
+
+    /// cvtsi2sd xmm0, rbx; movq rbx, xmm0
+    fn int_to_float_rbx(&mut self) {
+        self.asm.append(&mut vec![0xf2, 0x48, 0x0f, 0x2a, 0xc3, 0x66, 0x48, 0x0f, 0x7e, 0xc3]);
+        self.cur_pos();
+    }
+
+    /// movq xmm0, rax; cvttsd2si rax, xmm0 (с отбрасыванием дробной части)
+    fn float_to_int_rax(&mut self) {
+        self.asm.append(&mut vec![0x66, 0x48, 0x0f, 0x6e, 0xc0, 0xf2, 0x48, 0x0f, 0x2c, 0xc0]);
+        self.cur_pos();
+    }
+
+    /// mov qword [rbp+disp32], 0
+    fn mov_local_zero(&mut self, offset: i32) {
+        self.asm.append(&mut vec![0x48, 0xc7, 0x85]);
+        self.asm.append(&mut offset.to_le_bytes().to_vec());
+        self.asm.append(&mut vec![0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+    }
+
+    /// lea rdi, [rbp+disp32]; xor eax, eax; mov ecx, imm32; rep stosq
+    fn zero_local_array(&mut self, offset: i32, len: u64) {
+        self.asm.append(&mut vec![0x48, 0x8d, 0xbd]);
+        self.asm.append(&mut offset.to_le_bytes().to_vec());
+        self.asm.append(&mut vec![0x31, 0xc0, 0xb9]);
+        self.asm.append(&mut (len as u32).to_le_bytes().to_vec());
+        self.asm.append(&mut vec![0xf3, 0x48, 0xab]);
+        self.cur_pos();
+    }
+
+    /// push rbp; mov rbp, rsp; and rsp, -16; sub rsp, imm32.
+    /// Возвращает место размера кадра, который известен только в конце
+    fn enter(&mut self) -> u64 {
+        self.asm.append(&mut vec![0x55, 0x48, 0x89, 0xe5, 0x48, 0x83, 0xe4, 0xf0, 0x48, 0x81, 0xec, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.position - 4
+    }
+
+    fn patch_frame(&mut self, frame_position: u64) {
+        // sub rsp, imm32 расширяет размер со знаком
+        let size = (self.frame_size + 15) & !15;
+        self.asm[frame_position as usize..frame_position as usize + 4].copy_from_slice(&size.to_le_bytes());
+        self.frame = 0;
+        self.frame_size = 0;
+    }
+
+    /// leave; ret
+    fn leave(&mut self) {
+        self.asm.append(&mut vec![0xc9, 0xc3]);
+        self.cur_pos();
+    }
+
     fn push_rax(&mut self) {
         self.asm.push(0x50);
         self.cur_pos();
     }
 
+    fn push_rbx(&mut self) {
+        self.asm.push(0x53);
+        self.cur_pos();
+    }
+
     fn pop_rbx(&mut self) {
         self.asm.append(&mut vec![0x5b, 0x48, 0x93]);
         self.cur_pos();
@@ -483,11 +1851,6 @@ impl Semantic {
         self.cur_pos();
     }
 
-    fn or(&mut self) {
-        self.asm.append(&mut vec![0x48, 0x09, 0xd8]);
-        self.cur_pos();
-    }
-
     fn init_fpu(&mut self) {
         self.asm.append(&mut vec![0x9b, 0xdb, 0xe3]);
         self.asm.append(&mut vec![0x48, 0x89, 0x04, 0x25, 0x00, 0x00, 0x00, 0x00]);
@@ -549,17 +1912,54 @@ impl Semantic {
         self.cur_pos();
     }
 
+    /// cqo; idiv rbx; mov rax, rdx
+    fn rem_i64(&mut self) {
+        self.asm.append(&mut vec![0x48, 0x99, 0x48, 0xf7, 0xfb, 0x48, 0x89, 0xd0]);
+        self.cur_pos();
+    }
+
+    /// pow(rax, rbx) из libm. Вызов может стоять внутри вычисления
+    /// выражения, поэтому стек выравнивается, а rsp сохраняется в r12
+    fn pow_f64(&mut self) {
+        // movq xmm0, rax; movq xmm1, rbx; mov r12, rsp; and rsp, -16
+        self.asm.append(&mut vec![0x66, 0x48, 0x0f, 0x6e, 0xc0, 0x66, 0x48, 0x0f, 0x6e, 0xcb,
+            0x49, 0x89, 0xe4, 0x48, 0x83, 0xe4, 0xf0, 0xe8, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.asm_idents.push((self.reserve+11, self.position - 4, false));
+        // mov rsp, r12; movq rax, xmm0
+        self.asm.append(&mut vec![0x4c, 0x89, 0xe4, 0x66, 0x48, 0x0f, 0x7e, 0xc0]);
+        self.cur_pos();
+    }
+
     fn div_i64(&mut self) {
-        self.asm.append(&mut vec![0x48, 0xf7, 0xfb]);
+        // cqo: знаковое расширение rax в rdx перед idiv
+        self.asm.append(&mut vec![0x48, 0x99, 0x48, 0xf7, 0xfb]);
         self.cur_pos();
     }
 
     fn mov_rax_ident(&mut self, id: u64) {
+        if let Some(offset) = self.locals.get(&id).copied() {
+            // mov rax, [rbp+disp32]
+            self.asm.append(&mut vec![0x48, 0x8b, 0x85]);
+            self.asm.append(&mut offset.to_le_bytes().to_vec());
+            self.cur_pos();
+            return;
+        }
         self.asm.append(&mut vec![0x48, 0x8b, 0x04, 0x25, 0x00, 0x00, 0x00, 0x00]);
         self.cur_pos();
         self.asm_idents.push((id, self.position - 4, false));
     }
 
+    /// Значение константы в rax
+    fn immediate(&mut self, value: &ProgramTypes) {
+        match value {
+            ProgramTypes::Integer(Some(v)) => self.mov_rax_i64(*v),
+            ProgramTypes::Float(Some(v)) => self.mov_rax_f64(*v),
+            ProgramTypes::Boolean(Some(v)) => self.asm_bool(*v),
+            _ => ()
+        }
+    }
+
     fn asm_bool(&mut self, b: bool) {
         self.mov_rax_i64(if b {-1} else {0});
     }
@@ -580,9 +1980,14 @@ impl Semantic {
     fn jmp(&mut self, jmp_pos: u64) {
         let offset = (self.position - jmp_pos - 4) as i32;
         let bytes = offset.to_le_bytes();
-        for i in 0..4 {
-            self.asm[jmp_pos as usize + i] = bytes[i];
-        }
+        self.asm[jmp_pos as usize..jmp_pos as usize + 4].copy_from_slice(&bytes);
+    }
+
+    /// test rax, rax; jnz rel32
+    fn jnz_default(&mut self) -> u64 {
+        self.asm.append(&mut vec![0x48, 0x85, 0xc0, 0x0f, 0x85, 0x00, 0x00, 0x00, 0x00]);
+        self.cur_pos();
+        self.position - 4
     }
 
     fn jz_default(&mut self) -> u64 {
@@ -594,9 +1999,7 @@ impl Semantic {
     fn jz(&mut self, jz_pos: u64) {
         let offset = (self.position - jz_pos - 4) as i32;
         let bytes = offset.to_le_bytes();
-        for i in 0..4 {
-            self.asm[jz_pos as usize + i] = bytes[i];
-        }
+        self.asm[jz_pos as usize..jz_pos as usize + 4].copy_from_slice(&bytes);
     }
 
     fn cmp(&mut self) {
@@ -604,12 +2007,19 @@ impl Semantic {
         self.cur_pos();
     }
 
-    fn fcomi(&mut self) {
+    fn fucomi(&mut self) {
         self.init_fpu();
         self.asm.append(&mut vec![0xdd, 0x04, 0x25, 0x00, 0x00, 0x00, 0x00]);
         self.cur_pos();
         self.asm_idents.push((self.reserve, self.position - 4, false));
-        self.asm.append(&mut vec![0xdb, 0xf1]);
+        self.asm.append(&mut vec![0xdb, 0xe9]);
+        self.cur_pos();
+    }
+
+    /// jp к результату сравнения с NaN: перед условным переходом
+    /// из `jss_base` к `true` или сразу к `false` за ним
+    fn unordered(&mut self, result: bool) {
+        self.asm.append(&mut vec![0x7a, if result { 14 } else { 2 }]);
         self.cur_pos();
     }
 
@@ -643,6 +2053,26 @@ impl Semantic {
         self.jss_base();
     }
 
+    fn above(&mut self) {
+        self.asm.append(&mut vec![0x77, 12]);
+        self.jss_base();
+    }
+
+    fn below(&mut self) {
+        self.asm.append(&mut vec![0x72, 12]);
+        self.jss_base();
+    }
+
+    fn above_eq(&mut self) {
+        self.asm.append(&mut vec![0x73, 12]);
+        self.jss_base();
+    }
+
+    fn below_eq(&mut self) {
+        self.asm.append(&mut vec![0x76, 12]);
+        self.jss_base();
+    }
+
     fn jss_base(&mut self) {
         self.asm_bool(false);
         self.asm.append(&mut vec![0xeb, 10]);
@@ -651,3 +2081,11 @@ impl Semantic {
     }
 
 }
+
+/// Возвращает привязку имени, которая была до входа в блок
+fn restore<T>(map: &mut HashMap<u64, T>, id: u64, old: Option<T>) {
+    match old {
+        Some(v) => map.insert(id, v),
+        None => map.remove(&id)
+    };
+}
@@ -1,3 +1,4 @@
 pub mod tokens;
 pub mod types;
-pub mod program;
\ No newline at end of file
+pub mod program;
+pub mod span;
\ No newline at end of file
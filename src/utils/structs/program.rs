@@ -1,45 +1,136 @@
-use super::types::{AdditionOperations, MultiplicationOperations, ProgramTypes, RelationOperations};
+use super::{span::Span, types::{AdditionOperations, MultiplicationOperations, ProgramTypes, RelationOperations}};
 
 #[derive(Debug, Clone)]
 pub enum MainOperation {
-    CreateVariable(Vec<(Vec<u64>, ProgramTypes)>),
-    Operator(Operator)
+    CreateVariable(Vec<(Vec<Ident>, ProgramTypes)>),
+    /// Именованная константа `const N = выражение`
+    CreateConstant(Ident, Expression),
+    Operator(Operator),
+    Function(Function)
+}
+
+/// Процедура (`proc`) или функция (`func`) с результатом
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub ident: Ident,
+    pub params: Vec<(Ident, ProgramTypes)>,
+    pub result: Option<ProgramTypes>,
+    pub body: Vec<MainOperation>,
+    pub span: Span
 }
 
 #[derive(Debug, Clone)]
 pub enum Operator {
-    Composite(Vec<Operator>),
-    Assignment(u64, Expression),
+    /// Составной оператор, объявления в нём видны до конца блока
+    Composite(Vec<MainOperation>),
+    Assignment(Place, Expression),
     If(Expression, Box<Self>, Option<Box<Self>>),
-    For(Vec<Expression>, Box<Self>),
+    /// `for (начало; условие; шаг)`, где начало и шаг - присваивания
+    For(Option<Box<Self>>, Option<Expression>, Option<Box<Self>>, Box<Self>),
+    /// Старая форма `for`: все части заголовка - условия, объединённые через И
+    ForConditions(Vec<Expression>, Box<Self>),
     While(Expression, Box<Self>),
-    Input(Vec<u64>),
-    Output(Vec<Expression>)
+    /// `case x of 1: ...; 2, 3: ...; else ...; end_case`: метки - константные
+    /// выражения каждой ветви, затем необязательная ветвь `else`
+    Case(Expression, Vec<(Vec<Expression>, Self)>, Option<Box<Self>>),
+    Input(Vec<Place>),
+    Output(Vec<OutputItem>),
+    Break(Span),
+    Continue(Span),
+    Call(Ident, Vec<Expression>, Span),
+    Return(Option<Expression>, Span)
+}
+
+/// Элемент списка `output`: значение выводится с переводом строки,
+/// строковый литерал - как есть
+#[derive(Debug, Clone)]
+pub enum OutputItem {
+    Expression(Expression),
+    Text(u64, Span)
+}
+
+/// Переменная или элемент массива, которому присваивается значение
+#[derive(Debug, Clone)]
+pub enum Place {
+    Variable(Ident),
+    Element(Ident, Expression, Span)
+}
+
+impl Place {
+    pub fn ident(&self) -> Ident {
+        match self {
+            Place::Variable(ident) => *ident,
+            Place::Element(ident, _, _) => *ident
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Place::Variable(ident) => ident.span,
+            Place::Element(_, _, span) => *span
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ident {
+    pub id: u64,
+    pub span: Span
 }
 
 #[derive(Debug, Clone)]
 pub struct Expression {
     pub operands: Vec<Operand>,
-    pub operations: Vec<RelationOperations>
+    pub operations: Vec<RelationOperations>,
+    pub span: Span
 }
 
 #[derive(Debug, Clone)]
 pub struct Operand {
     pub terms: Vec<Term>,
-    pub operations: Vec<AdditionOperations>
+    pub operations: Vec<AdditionOperations>,
+    pub span: Span
 }
 
 #[derive(Debug, Clone)]
 pub struct Term {
     pub multipliers: Vec<Multiplier>,
-    pub operations: Vec<MultiplicationOperations>
+    pub operations: Vec<MultiplicationOperations>,
+    pub span: Span
 }
 
 #[derive(Debug, Clone)]
 pub enum Multiplier {
-    Identifier(u64),
-    Variable(u64),
-    Boolean(bool),
-    Not(Box<Self>),
-    Expression(Expression)
+    Identifier(Ident),
+    Variable(u64, Span),
+    Boolean(bool, Span),
+    Not(Box<Self>, Span),
+    Neg(Box<Self>, Span),
+    /// Явное преобразование `real(x)` или `integer(x)`
+    Convert(ProgramTypes, Expression, Span),
+    Call(Ident, Vec<Expression>, Span),
+    Index(Ident, Box<Expression>, Span),
+    /// Возведение в степень, правоассоциативное
+    Power(Box<Self>, Box<Self>, Span),
+    Expression(Expression),
+    /// Значение, вычисленное при компиляции с `-O1`
+    Constant(ProgramTypes, Span)
+}
+
+impl Multiplier {
+    pub fn span(&self) -> Span {
+        match self {
+            Multiplier::Identifier(ident) => ident.span,
+            Multiplier::Variable(_, span) => *span,
+            Multiplier::Boolean(_, span) => *span,
+            Multiplier::Not(_, span) => *span,
+            Multiplier::Neg(_, span) => *span,
+            Multiplier::Convert(_, _, span) => *span,
+            Multiplier::Call(_, _, span) => *span,
+            Multiplier::Index(_, _, span) => *span,
+            Multiplier::Power(_, _, span) => *span,
+            Multiplier::Expression(expression) => expression.span,
+            Multiplier::Constant(_, span) => *span
+        }
+    }
 }
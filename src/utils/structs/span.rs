@@ -0,0 +1,57 @@
+use std::{fmt::Debug, ops::Range};
+
+/// Участок исходного текста: строки и столбцы считаются с 1,
+/// конечный столбец не входит в участок. `start` и `end` - смещения
+/// в байтах от начала исходного текста. Все поля `u32`, чтобы ошибки
+/// с несколькими участками оставались компактными.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: u32,
+    pub end: u32,
+    pub line: u32,
+    pub column: u32,
+    pub end_line: u32,
+    pub end_column: u32
+}
+
+impl Span {
+    pub fn new(line: u32, column: u32, end_line: u32, end_column: u32) -> Self {
+        Self {
+            start: 0,
+            end: 0,
+            line,
+            column,
+            end_line,
+            end_column
+        }
+    }
+
+    pub fn with_offsets(mut self, start: usize, end: usize) -> Self {
+        self.start = start as u32;
+        self.end = end as u32;
+        self
+    }
+
+    /// Байтовый диапазон участка, пригодный для среза исходного текста
+    pub fn range(&self) -> Range<usize> {
+        self.start as usize..self.end as usize
+    }
+
+    /// Участок от начала `self` до конца `other`
+    pub fn to(self, other: Span) -> Self {
+        Self {
+            start: self.start,
+            end: other.end,
+            line: self.line,
+            column: self.column,
+            end_line: other.end_line,
+            end_column: other.end_column
+        }
+    }
+}
+
+impl Debug for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}..{}:{}", self.line, self.column, self.end_line, self.end_column)
+    }
+}
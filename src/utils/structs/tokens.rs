@@ -1,12 +1,16 @@
 use std::fmt::Display;
 
+use super::span::Span;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TokenGroupLexer {
     Keywords(KeywordsGroup),
     Delimiters(DelimitersGroup),
     Variables(String),
     Identifier(String),
-    Illegal((char, usize, usize)),
+    /// Содержимое строки после разбора управляющих последовательностей
+    String(String),
+    Illegal((char, u32, u32)),
     Eof
 }
 
@@ -16,22 +20,21 @@ pub enum TokenGroup {
     Delimiters(DelimitersGroup),
     Variables(u64),
     Identifier(u64),
+    Strings(u64),
     Eof
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Token {
     pub token: TokenGroup,
-    pub line: usize,
-    pub column: usize
+    pub span: Span
 }
 
 impl Token {
     pub fn eof() -> Self {
         Self {
             token: TokenGroup::Eof,
-            line: 0,
-            column: 0
+            span: Span::default()
         }
     }
 }
@@ -54,7 +57,19 @@ pub enum KeywordsGroup {
     While,
     Loop,
     Input,
-    Output
+    Output,
+    Break,
+    Continue,
+    Proc,
+    Func,
+    Return,
+    Array,
+    Of,
+    Mod,
+    Div,
+    Const,
+    Case,
+    EndCase
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -80,6 +95,9 @@ pub enum DelimitersGroup {
     Asterisk,          // "*"
     Slash,             // "/"
     Not,               // "!"
+    LeftSquareBracket, // "["
+    RightSquareBracket, // "]"
+    Power,             // "**" или "^"
     // Space,             // " "
 }
 
@@ -90,6 +108,7 @@ impl Display for TokenGroup {
             TokenGroup::Delimiters(delims) => write!(f, "(1, {})", delims.clone() as u8),
             TokenGroup::Variables(vars) => write!(f, "(2, {})", vars),
             TokenGroup::Identifier(id) => write!(f, "(3, {})", id),
+            TokenGroup::Strings(id) => write!(f, "(4, {})", id),
             TokenGroup::Eof => write!(f, "")
         }
     }
@@ -109,11 +128,7 @@ use std::ops::Shr;
 
 fn ordering(a: &DigitType, b: &DigitType) -> bool {
     let (a, b) = (a.clone() as u8, b.clone() as u8);
-    if b >= a {
-        true
-    } else {
-        false
-    }
+    b >= a
 }
 
 impl Shr for DigitType {
@@ -1,5 +1,7 @@
 use std::{fmt::Display, ops::BitAnd};
 
+use crate::utils::messages::tr;
+
 #[derive(Debug, Clone)]
 pub enum LexerDigitalData {
     Integer(i64),
@@ -19,7 +21,9 @@ impl Display for LexerDigitalData {
 pub enum ProgramTypes {
     Integer(Option<i64>),
     Float(Option<f64>),
-    Boolean(Option<bool>)
+    Boolean(Option<bool>),
+    /// Массив фиксированной длины из элементов простого типа
+    Array(Box<ProgramTypes>, u64)
 }
 
 impl BitAnd for ProgramTypes {
@@ -29,13 +33,15 @@ impl BitAnd for ProgramTypes {
         let a = match self {
             ProgramTypes::Integer(_) => 0,
             ProgramTypes::Float(_) => 1,
-            ProgramTypes::Boolean(_) => 2
+            ProgramTypes::Boolean(_) => 2,
+            ProgramTypes::Array(..) => 3
         };
 
         let b = match rhs {
             ProgramTypes::Integer(_) => 0,
             ProgramTypes::Float(_) => 1,
-            ProgramTypes::Boolean(_) => 2
+            ProgramTypes::Boolean(_) => 2,
+            ProgramTypes::Array(..) => 3
         };
 
         a==b
@@ -44,11 +50,12 @@ impl BitAnd for ProgramTypes {
 
 impl Display for ProgramTypes {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", match self {
-            ProgramTypes::Boolean(_) => "boolean",
-            ProgramTypes::Float(_) => "real",
-            ProgramTypes::Integer(_) => "integer"
-        })
+        match self {
+            ProgramTypes::Boolean(_) => f.write_str("boolean"),
+            ProgramTypes::Float(_) => f.write_str("real"),
+            ProgramTypes::Integer(_) => f.write_str("integer"),
+            ProgramTypes::Array(element, len) => write!(f, "array[{}] of {}", len, element)
+        }
     }
 }
 
@@ -62,9 +69,9 @@ pub enum RelationOperations {
     GreaterEqual
 }
 
-impl Into<String> for RelationOperations {
-    fn into(self) -> String {
-        match self {
+impl From<RelationOperations> for String {
+    fn from(val: RelationOperations) -> Self {
+        match val {
             RelationOperations::Equal => "==",
             RelationOperations::Greater => ">",
             RelationOperations::GreaterEqual => ">=",
@@ -78,12 +85,12 @@ impl Into<String> for RelationOperations {
 impl Display for RelationOperations {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", match self {
-            RelationOperations::Equal => "не равно",
-            RelationOperations::NotEqual => "равно",
-            RelationOperations::Less => "меньше",
-            RelationOperations::Greater => "больше",
-            RelationOperations::LessEqual => "меньше или равно",
-            RelationOperations::GreaterEqual => "больше или равно"
+            RelationOperations::Equal => tr("равно", "equal"),
+            RelationOperations::NotEqual => tr("не равно", "not equal"),
+            RelationOperations::Less => tr("меньше", "less"),
+            RelationOperations::Greater => tr("больше", "greater"),
+            RelationOperations::LessEqual => tr("меньше или равно", "less or equal"),
+            RelationOperations::GreaterEqual => tr("больше или равно", "greater or equal")
         })
     }
 }
@@ -98,9 +105,9 @@ pub enum AdditionOperations {
 impl Display for AdditionOperations {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", match self {
-            AdditionOperations::Addition => "сложение",
-            AdditionOperations::Subtraction => "вычитание",
-            AdditionOperations::Or => "или"
+            AdditionOperations::Addition => tr("сложение", "addition"),
+            AdditionOperations::Subtraction => tr("вычитание", "subtraction"),
+            AdditionOperations::Or => tr("или", "or")
         })
     }
 }
@@ -109,15 +116,21 @@ impl Display for AdditionOperations {
 pub enum MultiplicationOperations {
     Multiplication,
     Division,
+    /// Остаток от деления целых `mod`
+    Remainder,
+    /// Деление целых `div`
+    IntegerDivision,
     And
 }
 
 impl Display for MultiplicationOperations {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", match self {
-            MultiplicationOperations::Multiplication => "умножение",
-            MultiplicationOperations::Division => "деление",
-            MultiplicationOperations::And => "и"
+            MultiplicationOperations::Multiplication => tr("умножение", "multiplication"),
+            MultiplicationOperations::Division => tr("деление", "division"),
+            MultiplicationOperations::Remainder => tr("остаток от деления", "remainder"),
+            MultiplicationOperations::IntegerDivision => tr("целочисленное деление", "integer division"),
+            MultiplicationOperations::And => tr("и", "and")
         })
     }
 }
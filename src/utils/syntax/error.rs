@@ -1,9 +1,9 @@
-use crate::utils::structs::tokens::Token;
+use crate::utils::{messages::Message, structs::{span::Span, tokens::Token}};
 
 pub type SyntaxResult<T> = Result<T, SyntaxError>;
 
 #[derive(Debug, Clone)]
 pub enum SyntaxError {
-    Missing(Token, String),
-    Error(String)
+    Missing(Token, Message),
+    Error(Span, Message)
 }
\ No newline at end of file
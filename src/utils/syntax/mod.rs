@@ -1,29 +1,58 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::utils::structs::tokens::KeywordsGroup;
+use crate::utils::{messages::Message, structs::tokens::KeywordsGroup};
 
-use super::structs::{program::{Expression, MainOperation, Multiplier, Operand, Operator, Term}, tokens::{DelimitersGroup, Token, TokenGroup}, types::{AdditionOperations, LexerDigitalData, MultiplicationOperations, ProgramTypes, RelationOperations}};
+use super::structs::{program::{Expression, Function, Ident, MainOperation, Multiplier, Operand, Operator, OutputItem, Place, Term}, span::Span, tokens::{DelimitersGroup, Token, TokenGroup}, types::{AdditionOperations, LexerDigitalData, MultiplicationOperations, ProgramTypes, RelationOperations}};
 
 pub mod error;
 
 use error::{SyntaxResult, SyntaxError};
 
+/// Наибольшая длина массива: смещения элементов в кадре стека и в `.bss`
+/// задаются 32-битными числами со знаком
+pub const MAX_ARRAY_LEN: i64 = i32::MAX as i64 / 8;
+
+/// Часть заголовка `for` до выбора между новой и старой формой
+enum ForClause {
+    Assignment(Operator),
+    Condition(Expression)
+}
+
 #[derive(Debug, Clone)]
 pub struct Syntax {
     current_token: Token,
+    last_span: Span,
+    errors: Vec<SyntaxError>,
     pub position: usize,
 
     tokens: Vec<Token>,
-    vars: HashMap<u64, LexerDigitalData>
+    vars: HashMap<u64, LexerDigitalData>,
+    /// Имена всех процедур и функций программы: идентификатор
+    /// со скобкой после него разбирается как вызов
+    functions: HashSet<u64>,
+    in_function: bool,
+    /// Глубина вложенности составных операторов
+    depth: usize
+}
+
+impl Default for Syntax {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Syntax {
     pub fn new() -> Self {
         Self {
             current_token: Token::eof(),
+            last_span: Span::default(),
+            errors: Vec::new(),
             position: 0,
             tokens: Vec::default(),
-            vars: HashMap::default()
+            vars: HashMap::default(),
+            functions: HashSet::new(),
+            in_function: false,
+            depth: 0
         }
     }
 
@@ -31,9 +60,16 @@ impl Syntax {
         &mut self,
         tokens: Vec<Token>,
         vars: HashMap<u64, LexerDigitalData>
-    ) -> SyntaxResult<Vec<MainOperation>> {
+    ) -> (Vec<MainOperation>, Vec<SyntaxError>) {
         self.tokens = tokens;
         self.vars = vars;
+        self.errors.clear();
+        // вызовы могут стоять раньше объявления, поэтому имена
+        // процедур и функций собираются заранее
+        self.functions = self.tokens.windows(2).filter_map(|pair| match (&pair[0].token, &pair[1].token) {
+            (TokenGroup::Keywords(KeywordsGroup::Proc|KeywordsGroup::Func), TokenGroup::Identifier(id)) => Some(*id),
+            _ => None
+        }).collect();
 
         let mut main = Vec::new();
         self.read_token();
@@ -41,57 +77,63 @@ impl Syntax {
         match self.current_token.token {
             TokenGroup::Delimiters(DelimitersGroup::LeftCurlyBracket) => {
                 self.read_token();
-                while
-                    self.current_token.token != TokenGroup::Delimiters(DelimitersGroup::RightCurlyBracket)
-                    &&
-                    self.current_token.token != TokenGroup::Eof
-                {
-                    let res = self.get_main();
-                    if let Err(e) = res {
-                        return Err(e);
-                    }
-                    main.push(res.unwrap());
-                    match self.current_token.token {
-                        TokenGroup::Delimiters(DelimitersGroup::Semicolon) => self.read_token(),
-                        _ => return Err(SyntaxError::Missing(
-                            self.current_token.clone(),
-                            "Ожидалось ';'".to_string()
-                        ))
-                    }
-                }
+                main = self.get_block();
                 if self.current_token.token == TokenGroup::Eof {
-                    return Err(SyntaxError::Missing(
+                    self.errors.push(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидалось '}'".to_string()
+                        Message::ExpectedRightCurlyBracket
                     ));
                 } else {
-                    match self.current_token.token {
-                        TokenGroup::Delimiters(DelimitersGroup::RightCurlyBracket) =>
-                            self.read_token(),
-                        _ => return Err(SyntaxError::Missing(
-                            self.current_token.clone(),
-                            "Ожидалось '}'".to_string()
-                        ))
-                    }
+                    self.read_token();
                     if self.current_token.token != TokenGroup::Eof {
-                        return Err(SyntaxError::Missing(self.current_token.clone(), "Ожидался конец программы".to_string()));
+                        self.errors.push(SyntaxError::Missing(self.current_token.clone(), Message::ExpectedEndOfProgram));
                     }
                 }
             },
-            _ => return Err(SyntaxError::Missing(self.current_token.clone(), "Программа должна начинаться с '{'".to_string()))
+            _ => self.errors.push(SyntaxError::Missing(self.current_token.clone(), Message::ProgramStart))
+        }
+        if main.is_empty() && self.errors.is_empty() {
+            self.errors.push(SyntaxError::Error(self.last_span, Message::ExpectedDescriptionOrOperator));
         }
-        if main.len() == 0 {
-            return Err(SyntaxError::Error("Ожидалось описание или оператор".to_string()));
+        (main, std::mem::take(&mut self.errors))
+    }
+
+    /// Описания и операторы через `;` до закрывающей `}`, которая
+    /// остаётся текущей лексемой
+    fn get_block(&mut self) -> Vec<MainOperation> {
+        let mut main = Vec::new();
+        while
+            self.current_token.token != TokenGroup::Delimiters(DelimitersGroup::RightCurlyBracket)
+            &&
+            self.current_token.token != TokenGroup::Eof
+        {
+            match self.get_main() {
+                Ok(v) => main.push(v),
+                Err(e) => {
+                    self.errors.push(e);
+                    self.synchronize();
+                    if self.current_token.token == TokenGroup::Delimiters(DelimitersGroup::Semicolon) {
+                        self.read_token();
+                    }
+                    continue;
+                }
+            }
+            match self.current_token.token {
+                TokenGroup::Delimiters(DelimitersGroup::Semicolon) => self.read_token(),
+                _ => self.missing_separator(Message::ExpectedSemicolon)
+            }
         }
-        return Ok(main);
+        main
     }
 
     fn get_main(&mut self) -> SyntaxResult<MainOperation> {
         match self.current_token.token {
-            TokenGroup::Keywords(KeywordsGroup::Var) => match self.get_var() {
-                Ok(vars) => return Ok(MainOperation::CreateVariable(vars)),
-                Err(e) => return Err(e)
-            },
+            TokenGroup::Keywords(KeywordsGroup::Var) => Ok(MainOperation::CreateVariable(self.get_var())),
+            TokenGroup::Keywords(KeywordsGroup::Const) => self.get_const(),
+            TokenGroup::Keywords(KeywordsGroup::Proc|KeywordsGroup::Func) if self.in_function || self.depth > 0 =>
+                Err(SyntaxError::Error(self.current_token.span, Message::NestedFunction)),
+            TokenGroup::Keywords(KeywordsGroup::Proc|KeywordsGroup::Func) =>
+                Ok(MainOperation::Function(self.get_function()?)),
             _ => match self.get_operator() {
                 Ok(v) => Ok(MainOperation::Operator(v)),
                 Err(e) => Err(e)
@@ -99,66 +141,502 @@ impl Syntax {
         }
     }
 
-    fn get_var(&mut self) -> SyntaxResult<Vec<(Vec<u64>, ProgramTypes)>> {
+    fn get_var(&mut self) -> Vec<(Vec<Ident>, ProgramTypes)> {
         let mut vars = Vec::new();
         self.read_token();
-        
-        if self.current_token.token == TokenGroup::Delimiters(DelimitersGroup::Semicolon) {
-            return Ok(Vec::default());
-        }
-
-        let mut temp_vars = Vec::new();
-        let mut comma = false;
 
         loop {
             match self.current_token.token {
-                TokenGroup::Identifier(v) => {
-                    temp_vars.push(v);
-                    self.read_token();
-                    match self.current_token.token {
-                        TokenGroup::Delimiters(DelimitersGroup::Comma) =>
-                            comma = true,
-                        TokenGroup::Delimiters(DelimitersGroup::Colon) => {
-                            comma = false;
+                TokenGroup::Delimiters(DelimitersGroup::Semicolon)|
+                TokenGroup::Delimiters(DelimitersGroup::RightCurlyBracket)|
+                TokenGroup::Eof => return vars,
+                _ => match self.get_var_group() {
+                    Ok(group) => vars.push(group),
+                    Err(e) => {
+                        self.errors.push(e);
+                        while !matches!(
+                            self.current_token.token,
+                            TokenGroup::Delimiters(DelimitersGroup::Semicolon)|
+                            TokenGroup::Delimiters(DelimitersGroup::RightCurlyBracket)|
+                            TokenGroup::Eof
+                        ) {
                             self.read_token();
-                            match self.current_token.token {
-                                TokenGroup::Keywords(KeywordsGroup::Integer) =>
-                                    vars.push((temp_vars.clone(), ProgramTypes::Integer(None))),
-                                TokenGroup::Keywords(KeywordsGroup::Real) =>
-                                    vars.push((temp_vars.clone(), ProgramTypes::Float(None))),
-                                TokenGroup::Keywords(KeywordsGroup::Boolean) =>
-                                    vars.push((temp_vars.clone(), ProgramTypes::Boolean(None))),
-                                _ => return Err(SyntaxError::Missing(
-                                    self.current_token.clone(),
-                                    "Ожидался тип данных".to_string()
-                                ))
-                            }
-                            temp_vars.clear();
+                        }
+                        if self.current_token.token == TokenGroup::Delimiters(DelimitersGroup::Semicolon) {
                             self.read_token();
-                            match self.current_token.token {
-                                TokenGroup::Delimiters(DelimitersGroup::Semicolon) => (),
-                                _ => return Err(SyntaxError::Missing(
-                                    self.current_token.clone(),
-                                    "Ожидалась ';'".to_string()
-                                ))
-                            }
-                        },
-                        _ => return Err(SyntaxError::Error(
-                            "Встречена неожиданная лексема".to_string()
-                        ))
+                        }
                     }
-                },
-                TokenGroup::Delimiters(DelimitersGroup::Semicolon)|
-                TokenGroup::Delimiters(DelimitersGroup::RightCurlyBracket) => match comma {
-                    false => return Ok(vars),
+                }
+            }
+        }
+    }
+
+    /// `const имя = выражение`, тип константы определяется по значению
+    fn get_const(&mut self) -> SyntaxResult<MainOperation> {
+        self.read_token();
+        let ident = match self.current_token.token {
+            TokenGroup::Identifier(id) => Ident { id, span: self.current_token.span },
+            _ => return Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedIdentifier
+            ))
+        };
+        self.read_token();
+        match self.current_token.token {
+            TokenGroup::Delimiters(DelimitersGroup::Equal) => self.read_token(),
+            _ => return Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedEqual
+            ))
+        }
+        Ok(MainOperation::CreateConstant(ident, self.get_expression()?))
+    }
+
+    /// `proc имя(параметры) { ... }` или `func имя(параметры): тип { ... }`
+    fn get_function(&mut self) -> SyntaxResult<Function> {
+        let start = self.current_token.span;
+        let is_func = self.current_token.token == TokenGroup::Keywords(KeywordsGroup::Func);
+        self.read_token();
+
+        let ident = match self.current_token.token {
+            TokenGroup::Identifier(id) => Ident { id, span: self.current_token.span },
+            _ => return Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedIdentifier
+            ))
+        };
+        self.read_token();
+        match self.current_token.token {
+            TokenGroup::Delimiters(DelimitersGroup::LeftParenthesis) => self.read_token(),
+            _ => return Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedLeftParenthesis
+            ))
+        }
+        let params = self.get_params()?;
+
+        let result = if is_func {
+            match self.current_token.token {
+                TokenGroup::Delimiters(DelimitersGroup::Colon) => self.read_token(),
+                _ => return Err(SyntaxError::Missing(
+                    self.current_token.clone(),
+                    Message::ExpectedColon
+                ))
+            }
+            let result = self.get_type()?;
+            self.read_token();
+            Some(result)
+        } else {
+            None
+        };
+
+        match self.current_token.token {
+            TokenGroup::Delimiters(DelimitersGroup::LeftCurlyBracket) => self.read_token(),
+            _ => return Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedLeftCurlyBracket
+            ))
+        }
+        self.in_function = true;
+        let body = self.get_block();
+        self.in_function = false;
+        if self.current_token.token == TokenGroup::Eof {
+            return Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedRightCurlyBracket
+            ));
+        }
+        self.read_token();
+
+        Ok(Function {
+            ident,
+            params,
+            result,
+            body,
+            span: start.to(self.last_span)
+        })
+    }
+
+    /// Параметры `a, b: integer; c: real` до закрывающей скобки включительно
+    fn get_params(&mut self) -> SyntaxResult<Vec<(Ident, ProgramTypes)>> {
+        let mut params = Vec::new();
+        if self.current_token.token == TokenGroup::Delimiters(DelimitersGroup::RightParenthesis) {
+            self.read_token();
+            return Ok(params);
+        }
+
+        loop {
+            let mut identifiers = Vec::new();
+            loop {
+                match self.current_token.token {
+                    TokenGroup::Identifier(id) =>
+                        identifiers.push(Ident { id, span: self.current_token.span }),
+                    _ => return Err(SyntaxError::Missing(
+                        self.current_token.clone(),
+                        Message::ExpectedIdentifier
+                    ))
+                }
+                self.read_token();
+                match self.current_token.token {
+                    TokenGroup::Delimiters(DelimitersGroup::Comma) => self.read_token(),
+                    TokenGroup::Delimiters(DelimitersGroup::Colon) => {
+                        self.read_token();
+                        break;
+                    },
                     _ => return Err(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидался идентификатор".to_string()
+                        Message::ExpectedColonOrComma
                     ))
+                }
+            }
+            let param_type = self.get_type()?;
+            params.extend(identifiers.into_iter().map(|ident| (ident, param_type.clone())));
+            self.read_token();
+            match self.current_token.token {
+                TokenGroup::Delimiters(DelimitersGroup::Semicolon) => self.read_token(),
+                TokenGroup::Delimiters(DelimitersGroup::RightParenthesis) => {
+                    self.read_token();
+                    return Ok(params);
                 },
-                _ => return Err(SyntaxError::Missing(self.current_token.clone(), "Ожидался идентификатор или ';'".to_string()))
+                _ => return Err(SyntaxError::Missing(
+                    self.current_token.clone(),
+                    Message::ExpectedSemicolonOrRightParenthesis
+                ))
+            }
+        }
+    }
+
+    /// Тип данных в текущей лексеме, лексема не пропускается
+    fn get_type(&self) -> SyntaxResult<ProgramTypes> {
+        match self.current_token.token {
+            TokenGroup::Keywords(KeywordsGroup::Integer) => Ok(ProgramTypes::Integer(None)),
+            TokenGroup::Keywords(KeywordsGroup::Real) => Ok(ProgramTypes::Float(None)),
+            TokenGroup::Keywords(KeywordsGroup::Boolean) => Ok(ProgramTypes::Boolean(None)),
+            _ => Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedType
+            ))
+        }
+    }
+
+    /// Тип переменной: простой тип или `array[N] of тип`.
+    /// Последняя лексема типа не пропускается
+    fn get_var_type(&mut self) -> SyntaxResult<ProgramTypes> {
+        if self.current_token.token != TokenGroup::Keywords(KeywordsGroup::Array) {
+            return self.get_type();
+        }
+        self.read_token();
+        match self.current_token.token {
+            TokenGroup::Delimiters(DelimitersGroup::LeftSquareBracket) => self.read_token(),
+            _ => return Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedLeftSquareBracket
+            ))
+        }
+        let len = match self.current_token.token {
+            TokenGroup::Variables(id) => match self.vars.get(&id) {
+                Some(LexerDigitalData::Integer(len)) if *len > MAX_ARRAY_LEN => return Err(SyntaxError::Error(
+                    self.current_token.span,
+                    Message::ArrayTooLarge(MAX_ARRAY_LEN)
+                )),
+                Some(LexerDigitalData::Integer(len)) if *len > 0 => *len as u64,
+                _ => return Err(SyntaxError::Error(self.current_token.span, Message::ArraySize))
+            },
+            _ => return Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ArraySize
+            ))
+        };
+        self.read_token();
+        match self.current_token.token {
+            TokenGroup::Delimiters(DelimitersGroup::RightSquareBracket) => self.read_token(),
+            _ => return Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedRightSquareBracket
+            ))
+        }
+        match self.current_token.token {
+            TokenGroup::Keywords(KeywordsGroup::Of) => self.read_token(),
+            _ => return Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedOf
+            ))
+        }
+        Ok(ProgramTypes::Array(Box::new(self.get_type()?), len))
+    }
+
+    /// Индекс в квадратных скобках после имени массива. Текущая лексема `[`,
+    /// после разбора текущей остаётся `]`
+    fn get_index(&mut self) -> SyntaxResult<Expression> {
+        self.read_token();
+        let index = self.get_expression()?;
+        match self.current_token.token {
+            TokenGroup::Delimiters(DelimitersGroup::RightSquareBracket) => Ok(index),
+            _ => Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedRightSquareBracket
+            ))
+        }
+    }
+
+    /// Переменная или элемент массива после уже прочитанного имени
+    fn get_place(&mut self, ident: Ident) -> SyntaxResult<Place> {
+        if self.current_token.token != TokenGroup::Delimiters(DelimitersGroup::LeftSquareBracket) {
+            return Ok(Place::Variable(ident));
+        }
+        let index = self.get_index()?;
+        self.read_token();
+        Ok(Place::Element(ident, index, ident.span.to(self.last_span)))
+    }
+
+    /// Аргументы вызова через запятую. Текущая лексема `(`,
+    /// после разбора текущей остаётся `)`
+    fn get_arguments(&mut self) -> SyntaxResult<Vec<Expression>> {
+        let mut arguments = Vec::new();
+        self.read_token();
+        if self.current_token.token == TokenGroup::Delimiters(DelimitersGroup::RightParenthesis) {
+            return Ok(arguments);
+        }
+        loop {
+            arguments.push(self.get_expression()?);
+            match self.current_token.token {
+                TokenGroup::Delimiters(DelimitersGroup::Comma) => self.read_token(),
+                TokenGroup::Delimiters(DelimitersGroup::RightParenthesis) => return Ok(arguments),
+                _ => return Err(SyntaxError::Missing(
+                    self.current_token.clone(),
+                    Message::ExpectedCommaOrRightParenthesis
+                ))
+            }
+        }
+    }
+
+    fn get_var_group(&mut self) -> SyntaxResult<(Vec<Ident>, ProgramTypes)> {
+        let mut identifiers = Vec::new();
+
+        loop {
+            match self.current_token.token {
+                TokenGroup::Identifier(id) =>
+                    identifiers.push(Ident { id, span: self.current_token.span }),
+                _ if identifiers.is_empty() => return Err(SyntaxError::Missing(
+                    self.current_token.clone(),
+                    Message::ExpectedIdentifierOrSemicolon
+                )),
+                _ => return Err(SyntaxError::Missing(
+                    self.current_token.clone(),
+                    Message::ExpectedIdentifier
+                ))
             }
             self.read_token();
+            match self.current_token.token {
+                TokenGroup::Delimiters(DelimitersGroup::Comma) => self.read_token(),
+                TokenGroup::Delimiters(DelimitersGroup::Colon) => {
+                    self.read_token();
+                    break;
+                },
+                _ => return Err(SyntaxError::Missing(
+                    self.current_token.clone(),
+                    Message::ExpectedColonOrComma
+                ))
+            }
+        }
+
+        let var_type = self.get_var_type()?;
+        self.read_token();
+        match self.current_token.token {
+            TokenGroup::Delimiters(DelimitersGroup::Semicolon) => self.read_token(),
+            _ => return Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedSemicolon
+            ))
+        }
+
+        Ok((identifiers, var_type))
+    }
+
+    /// Ветвь `case`: метки через запятую, двоеточие и оператор
+    fn get_case_arm(&mut self) -> SyntaxResult<(Vec<Expression>, Operator)> {
+        let mut labels = Vec::new();
+        loop {
+            labels.push(self.get_expression()?);
+            match self.current_token.token {
+                TokenGroup::Delimiters(DelimitersGroup::Comma) => self.read_token(),
+                TokenGroup::Delimiters(DelimitersGroup::Colon) => {
+                    self.read_token();
+                    break;
+                },
+                _ => return Err(SyntaxError::Missing(
+                    self.current_token.clone(),
+                    Message::ExpectedColonOrComma
+                ))
+            }
+        }
+        Ok((labels, self.get_operator()?))
+    }
+
+    /// Присваивание `[let] имя = выражение` или `[let] имя[индекс] = выражение`
+    fn get_assignment(&mut self) -> SyntaxResult<Operator> {
+        if self.current_token.token == TokenGroup::Keywords(KeywordsGroup::Let) {
+            self.read_token();
+        }
+        let ident = match self.current_token.token {
+            TokenGroup::Identifier(id) => Ident { id, span: self.current_token.span },
+            _ => return Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedIdentifier
+            ))
+        };
+        self.read_token();
+        let place = self.get_place(ident)?;
+        match self.current_token.token {
+            TokenGroup::Delimiters(DelimitersGroup::Equal) => self.read_token(),
+            _ => return Err(SyntaxError::Missing(
+                self.current_token.clone(),
+                Message::ExpectedEqual
+            ))
+        }
+        Ok(Operator::Assignment(place, self.get_expression()?))
+    }
+
+    /// Начинается ли с текущей лексемы присваивание: после имени и,
+    /// возможно, индекса в скобках стоит `=`
+    fn is_assignment(&self) -> bool {
+        match self.current_token.token {
+            TokenGroup::Keywords(KeywordsGroup::Let) => true,
+            TokenGroup::Identifier(_) => {
+                let mut position = self.position;
+                if self.next_token().token == TokenGroup::Delimiters(DelimitersGroup::LeftSquareBracket) {
+                    let mut depth = 0;
+                    while let Some(token) = self.tokens.get(position) {
+                        position += 1;
+                        match token.token {
+                            TokenGroup::Delimiters(DelimitersGroup::LeftSquareBracket) => depth += 1,
+                            TokenGroup::Delimiters(DelimitersGroup::RightSquareBracket) => depth -= 1,
+                            _ => ()
+                        }
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                }
+                matches!(
+                    self.tokens.get(position).map(|token| &token.token),
+                    Some(TokenGroup::Delimiters(DelimitersGroup::Equal))
+                )
+            },
+            _ => false
+        }
+    }
+
+    /// Начало или шаг заголовка `for` до лексемы `end`
+    fn get_for_clause(&mut self, end: DelimitersGroup) -> SyntaxResult<Option<(ForClause, Span)>> {
+        if self.current_token.token == TokenGroup::Delimiters(end) {
+            return Ok(None);
+        }
+        let start = self.current_token.span;
+        let clause = if self.is_assignment() {
+            ForClause::Assignment(self.get_assignment()?)
+        } else {
+            ForClause::Condition(self.get_expression()?)
+        };
+        Ok(Some((clause, start.to(self.last_span))))
+    }
+
+    /// Если начало или шаг - условие, заголовок разбирается в старой форме,
+    /// где все три части - условия, которые должны выполняться вместе
+    fn make_for(
+        init: Option<(ForClause, Span)>,
+        condition: Option<Expression>,
+        step: Option<(ForClause, Span)>,
+        body: Box<Operator>
+    ) -> SyntaxResult<Operator> {
+        let legacy = [&init, &step].iter()
+            .any(|clause| matches!(clause, Some((ForClause::Condition(_), _))));
+        if legacy {
+            let mut conditions = Vec::new();
+            conditions.extend(init.map(Self::for_condition).transpose()?);
+            conditions.extend(condition);
+            conditions.extend(step.map(Self::for_condition).transpose()?);
+            return Ok(Operator::ForConditions(conditions, body));
+        }
+
+        let assignment = |clause: Option<(ForClause, Span)>| clause.map(|(clause, _)| match clause {
+            ForClause::Assignment(operator) => Box::new(operator),
+            ForClause::Condition(_) => unreachable!()
+        });
+        Ok(Operator::For(assignment(init), condition, assignment(step), body))
+    }
+
+    /// Часть заголовка старой формы `for`, присваивание в ней недопустимо
+    fn for_condition((clause, span): (ForClause, Span)) -> SyntaxResult<Expression> {
+        match clause {
+            ForClause::Condition(expression) => Ok(expression),
+            ForClause::Assignment(_) => Err(SyntaxError::Error(span, Message::ForClauses))
+        }
+    }
+
+    /// Пропускает лексемы до ближайшей точки синхронизации: `;`, `}`,
+    /// `end_else`, `end_case` или `loop`. Вложенные блоки `{ ... }` пропускаются
+    /// целиком, а `end_else`, `end_case` и `loop` считаются концом ошибочного оператора.
+    fn synchronize(&mut self) {
+        loop {
+            match self.current_token.token {
+                TokenGroup::Delimiters(DelimitersGroup::Semicolon)|
+                TokenGroup::Delimiters(DelimitersGroup::RightCurlyBracket)|
+                TokenGroup::Eof => return,
+                TokenGroup::Keywords(KeywordsGroup::EndElse)|
+                TokenGroup::Keywords(KeywordsGroup::EndCase)|
+                TokenGroup::Keywords(KeywordsGroup::Loop) => {
+                    self.read_token();
+                    return;
+                },
+                TokenGroup::Delimiters(DelimitersGroup::LeftCurlyBracket) => {
+                    let mut depth = 0;
+                    loop {
+                        match self.current_token.token {
+                            TokenGroup::Delimiters(DelimitersGroup::LeftCurlyBracket) => depth += 1,
+                            TokenGroup::Delimiters(DelimitersGroup::RightCurlyBracket) => depth -= 1,
+                            TokenGroup::Eof => return,
+                            _ => ()
+                        }
+                        self.read_token();
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                },
+                _ => self.read_token()
+            }
+        }
+    }
+
+    /// Сообщает об отсутствующем разделителе после оператора. Если дальше
+    /// начинается новый оператор, разбор продолжается с него.
+    fn missing_separator(&mut self, text: Message) {
+        self.errors.push(SyntaxError::Missing(self.current_token.clone(), text));
+        let starts_operator = matches!(
+            self.current_token.token,
+            TokenGroup::Identifier(_)|
+            TokenGroup::Keywords(KeywordsGroup::Var)|
+            TokenGroup::Keywords(KeywordsGroup::Const)|
+            TokenGroup::Keywords(KeywordsGroup::Let)|
+            TokenGroup::Keywords(KeywordsGroup::If)|
+            TokenGroup::Keywords(KeywordsGroup::For)|
+            TokenGroup::Keywords(KeywordsGroup::Case)|
+            TokenGroup::Keywords(KeywordsGroup::Do)|
+            TokenGroup::Keywords(KeywordsGroup::Input)|
+            TokenGroup::Keywords(KeywordsGroup::Output)|
+            TokenGroup::Keywords(KeywordsGroup::Break)|
+            TokenGroup::Keywords(KeywordsGroup::Continue)|
+            TokenGroup::Keywords(KeywordsGroup::Proc)|
+            TokenGroup::Keywords(KeywordsGroup::Func)|
+            TokenGroup::Keywords(KeywordsGroup::Return)|
+            TokenGroup::Delimiters(DelimitersGroup::LeftCurlyBracket)
+        );
+        if !starts_operator {
+            self.synchronize();
+            if self.current_token.token == TokenGroup::Delimiters(DelimitersGroup::Semicolon) {
+                self.read_token();
+            }
         }
     }
 
@@ -166,91 +644,77 @@ impl Syntax {
         match self.current_token.token.clone() {
             TokenGroup::Delimiters(DelimitersGroup::LeftCurlyBracket) => {
                 self.read_token();
+                self.depth += 1;
                 let mut operators = Vec::new();
                 while
                     self.current_token.token != TokenGroup::Delimiters(DelimitersGroup::RightCurlyBracket)
                     &&
                     self.current_token.token != TokenGroup::Eof
                 {
-                    match self.get_operator() {
+                    match self.get_main() {
                         Ok(v) => operators.push(v),
-                        Err(e) => return Err(e)
+                        Err(e) => {
+                            self.errors.push(e);
+                            self.synchronize();
+                            if self.current_token.token == TokenGroup::Delimiters(DelimitersGroup::Semicolon) {
+                                self.read_token();
+                            }
+                            continue;
+                        }
                     }
                     if self.current_token.token == TokenGroup::Delimiters(DelimitersGroup::Semicolon) {
-                        match self.next_token().token {
-                            TokenGroup::Delimiters(DelimitersGroup::RightCurlyBracket) =>
-                                return Err(SyntaxError::Error("Ожидался оператор".to_string())),
-                            _ => self.read_token(),
+                        if let TokenGroup::Delimiters(DelimitersGroup::RightCurlyBracket) = self.next_token().token {
+                            self.errors.push(SyntaxError::Error(self.current_token.span, Message::ExpectedOperator));
                         }
+                        self.read_token();
                     } else if self.current_token.token != TokenGroup::Delimiters(DelimitersGroup::RightCurlyBracket) {
-                        return Err(SyntaxError::Missing(
-                            self.current_token.clone(),
-                            "Ожидалось '}'".to_string()
-                        ));
+                        self.missing_separator(Message::ExpectedRightCurlyBracket);
                     }
                 }
+                self.depth -= 1;
                 self.read_token();
-                return  Ok(Operator::Composite(operators));
+                Ok(Operator::Composite(operators))
             },
-            TokenGroup::Keywords(KeywordsGroup::Let) => {
+            TokenGroup::Keywords(KeywordsGroup::Let) => self.get_assignment(),
+            TokenGroup::Identifier(id) => {
+                let ident = Ident { id, span: self.current_token.span };
                 self.read_token();
                 match self.current_token.token {
-                    TokenGroup::Identifier(id) => {
+                    TokenGroup::Delimiters(DelimitersGroup::LeftParenthesis) if self.functions.contains(&id) => {
+                        let arguments = self.get_arguments()?;
                         self.read_token();
+                        Ok(Operator::Call(ident, arguments, ident.span.to(self.last_span)))
+                    },
+                    _ => {
+                        let place = self.get_place(ident)?;
                         match self.current_token.token {
                             TokenGroup::Delimiters(DelimitersGroup::Equal) => {
                                 self.read_token();
                                 match self.get_expression() {
-                                    Ok(v) => return Ok(Operator::Assignment(id, v)),
-                                    Err(e) => return Err(e)
+                                    Ok(v) => Ok(Operator::Assignment(place, v)),
+                                    Err(e) => Err(e)
                                 }
                             },
-                            _ => return Err(SyntaxError::Missing(
+                            _ => Err(SyntaxError::Missing(
                                 self.current_token.clone(),
-                                "Ожидался знак '='".to_string()
+                                Message::ExpectedEqual
                             ))
                         }
-                    },
-                    _ => return Err(SyntaxError::Missing(
-                        self.current_token.clone(),
-                        "Ожидалось идентификатор".to_string()
-                    ))
-                }
-            },
-            TokenGroup::Identifier(id) => {
-                self.read_token();
-                match self.current_token.token {
-                    TokenGroup::Delimiters(DelimitersGroup::Equal) => {
-                        self.read_token();
-                        match self.get_expression() {
-                            Ok(v) => return Ok(Operator::Assignment(id, v)),
-                            Err(e) => return Err(e)
-                        }
-                    },
-                    _ => return Err(SyntaxError::Missing(
-                        self.current_token.clone(),
-                        "Ожидался знак '='".to_string()
-                    ))
+                    }
                 }
             },
             TokenGroup::Keywords(KeywordsGroup::If) => {
                 self.read_token();
-                let expression = match self.get_expression() {
-                    Err(e) => return Err(e),
-                    Ok(expression) => expression
-                };
+                let expression = self.get_expression()?;
                 match self.current_token.token {
                     TokenGroup::Keywords(KeywordsGroup::Then) => (),
                     _ => return Err(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидалось 'then'".to_string()
+                        Message::ExpectedThen
                     ))
                 }
                 self.read_token();
-                let operator1 = match self.get_operator() {
-                    Ok(operator) => operator,
-                    Err(e) => return Err(e),
-                };
+                let operator1 = self.get_operator()?;
                 let operator2 = match self.current_token.token {
                     TokenGroup::Keywords(KeywordsGroup::Else) => {
                         self.read_token();
@@ -265,7 +729,7 @@ impl Syntax {
                     TokenGroup::Keywords(KeywordsGroup::EndElse) => (),
                     _ => return Err(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидалось 'end_else'".to_string()
+                        Message::ExpectedEndElse
                     ))
                 }
                 self.read_token();
@@ -275,69 +739,85 @@ impl Syntax {
                     operator2
                 ))
             }
+            TokenGroup::Keywords(KeywordsGroup::Case) => {
+                self.read_token();
+                let selector = self.get_expression()?;
+                match self.current_token.token {
+                    TokenGroup::Keywords(KeywordsGroup::Of) => self.read_token(),
+                    _ => return Err(SyntaxError::Missing(
+                        self.current_token.clone(),
+                        Message::ExpectedOf
+                    ))
+                }
+
+                let mut arms = Vec::new();
+                let mut default = None;
+                while self.current_token.token != TokenGroup::Keywords(KeywordsGroup::EndCase) {
+                    if default.is_some() {
+                        return Err(SyntaxError::Missing(
+                            self.current_token.clone(),
+                            Message::ExpectedEndCase
+                        ));
+                    }
+                    if self.current_token.token == TokenGroup::Keywords(KeywordsGroup::Else) {
+                        self.read_token();
+                        default = Some(Box::new(self.get_operator()?));
+                    } else {
+                        arms.push(self.get_case_arm()?);
+                    }
+                    match self.current_token.token {
+                        TokenGroup::Delimiters(DelimitersGroup::Semicolon) => self.read_token(),
+                        TokenGroup::Keywords(KeywordsGroup::EndCase) => (),
+                        _ => return Err(SyntaxError::Missing(
+                            self.current_token.clone(),
+                            Message::ExpectedEndCase
+                        ))
+                    }
+                }
+                self.read_token();
+                Ok(Operator::Case(selector, arms, default))
+            },
             TokenGroup::Keywords(KeywordsGroup::For) => {
                 self.read_token();
                 match self.current_token.token {
                     TokenGroup::Delimiters(DelimitersGroup::LeftParenthesis) => self.read_token(),
                     _ => return Err(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидалась '('".to_string()
+                        Message::ExpectedLeftParenthesis
                     ))
                 };
-                
-                let mut expressions = Vec::new();
-                match self.current_token.token {
-                    TokenGroup::Delimiters(DelimitersGroup::Semicolon) => (),
-                    _ => match self.get_expression() {
-                        Ok(v) => expressions.push(v),
-                        Err(e) => return Err(e)
-                    }
-                }
-                
+
+                let init = self.get_for_clause(DelimitersGroup::Semicolon)?;
                 match self.current_token.token {
                     TokenGroup::Delimiters(DelimitersGroup::Semicolon) => self.read_token(),
                     _ => return Err(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидалась ';' или выражение".to_string()
+                        Message::ExpectedSemicolonOrExpression
                     ))
                 }
-                match self.current_token.token {
-                    TokenGroup::Delimiters(DelimitersGroup::Semicolon) => (),
-                    _ => match self.get_expression() {
-                        Ok(v) => expressions.push(v),
-                        Err(e) => return Err(e)
-                    }
-                }
+                let condition = match self.current_token.token {
+                    TokenGroup::Delimiters(DelimitersGroup::Semicolon) => None,
+                    _ => Some(self.get_expression()?)
+                };
                 match self.current_token.token {
                     TokenGroup::Delimiters(DelimitersGroup::Semicolon) => self.read_token(),
                     _ => return Err(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидалась ';' или выражение".to_string()
+                        Message::ExpectedSemicolonOrExpression
                     ))
                 }
-                match self.current_token.token {
-                    TokenGroup::Delimiters(DelimitersGroup::RightParenthesis) => (),
-                    _ => match self.get_expression() {
-                        Ok(v) => expressions.push(v),
-                        Err(e) => return Err(e)
-                    }
-                }
+                let step = self.get_for_clause(DelimitersGroup::RightParenthesis)?;
                 match self.current_token.token {
                     TokenGroup::Delimiters(DelimitersGroup::RightParenthesis) =>
                         self.read_token(),
                     _ => return Err(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидалась ')'".to_string()
+                        Message::ExpectedRightParenthesis
                     ))
                 };
-                
-                match self.get_operator() {
-                    Ok(op) => Ok(Operator::For(
-                        expressions,
-                        Box::new(op)
-                    )),
-                    Err(e) => Err(e)
-                }
+
+                let body = Box::new(self.get_operator()?);
+                Self::make_for(init, condition, step, body)
             },
             TokenGroup::Keywords(KeywordsGroup::Do) => {
                 self.read_token();
@@ -346,19 +826,13 @@ impl Syntax {
                         self.read_token(),
                     _ => return Err(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидалось ключевое слово 'while'".to_string()
+                        Message::ExpectedWhile
                     ))
                 };
 
-                let expressions = match self.get_expression() {
-                    Ok(v) => v,
-                    Err(e) => return Err(e)
-                };
+                let expressions = self.get_expression()?;
 
-                let operator = match self.get_operator() {
-                    Ok(operator) => operator,
-                    Err(e) => return Err(e)
-                };
+                let operator = self.get_operator()?;
                 
                 match self.current_token.token {
                     TokenGroup::Keywords(KeywordsGroup::Loop) => {
@@ -370,7 +844,7 @@ impl Syntax {
                     },
                     _ => Err(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидалась 'loop'".to_string()
+                        Message::ExpectedLoop
                     ))
                 }
             },
@@ -381,7 +855,7 @@ impl Syntax {
                         self.read_token(),
                     _ => return Err(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидалась '('".to_string()
+                        Message::ExpectedLeftParenthesis
                     ))
                 };
                 let mut identifiers = Vec::new();
@@ -390,20 +864,21 @@ impl Syntax {
                     match self.current_token.token.clone() {
                         TokenGroup::Delimiters(DelimitersGroup::RightParenthesis) => break,
                         TokenGroup::Identifier(id) => {
+                            let ident = Ident { id, span: self.current_token.span };
                             self.read_token();
-                            identifiers.push(id);
+                            identifiers.push(self.get_place(ident)?);
                         },
                         _ => return Err(SyntaxError::Missing(
                             self.current_token.clone(),
-                            "Ожидался идентификатор или ')'".to_string()
+                            Message::ExpectedIdentifierOrRightParenthesis
                         ))
                     };
                 }
 
-                if identifiers.len() == 0 {
+                if identifiers.is_empty() {
                     return Err(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидался идентификатор".to_string()
+                        Message::ExpectedIdentifier
                     ));
                 }
 
@@ -417,63 +892,133 @@ impl Syntax {
                         self.read_token(),
                     _ => return Err(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидалась '('".to_string()
+                        Message::ExpectedLeftParenthesis
                     ))
                 };
 
-                let mut expressions = Vec::new();
+                let mut items = Vec::new();
 
                 loop {
                     match self.current_token.token {
                         TokenGroup::Delimiters(DelimitersGroup::RightParenthesis) =>
                             break,
+                        TokenGroup::Strings(id) => {
+                            items.push(OutputItem::Text(id, self.current_token.span));
+                            self.read_token();
+                        },
                         _ => match self.get_expression() {
-                            Ok(expression) => expressions.push(expression),
+                            Ok(expression) => items.push(OutputItem::Expression(expression)),
                             Err(e) => return  Err(e)
                         }
                     };
                 }
 
-                if expressions.len() == 0 {
+                if items.is_empty() {
                     return Err(SyntaxError::Missing(
                         self.current_token.clone(),
-                        "Ожидался оператор".to_string()
+                        Message::ExpectedOperator
                     ));
                 }
 
                 self.read_token();
-                Ok(Operator::Output(expressions))
+                Ok(Operator::Output(items))
+            },
+            TokenGroup::Keywords(KeywordsGroup::Break) => {
+                let span = self.current_token.span;
+                self.read_token();
+                Ok(Operator::Break(span))
+            },
+            TokenGroup::Keywords(KeywordsGroup::Continue) => {
+                let span = self.current_token.span;
+                self.read_token();
+                Ok(Operator::Continue(span))
+            },
+            TokenGroup::Keywords(KeywordsGroup::Return) => {
+                let span = self.current_token.span;
+                self.read_token();
+                match self.current_token.token {
+                    TokenGroup::Delimiters(DelimitersGroup::Semicolon)|
+                    TokenGroup::Delimiters(DelimitersGroup::RightCurlyBracket)|
+                    TokenGroup::Keywords(KeywordsGroup::Else)|
+                    TokenGroup::Keywords(KeywordsGroup::EndElse)|
+                    TokenGroup::Keywords(KeywordsGroup::Loop)|
+                    TokenGroup::Eof => Ok(Operator::Return(None, span)),
+                    _ => {
+                        let expression = self.get_expression()?;
+                        let span = span.to(expression.span);
+                        Ok(Operator::Return(Some(expression), span))
+                    }
+                }
             },
-            t => Err(SyntaxError::Error(format!("Встречена непредвиденная лексема {}", t)))
+            t => Err(SyntaxError::Error(self.current_token.span, Message::UnexpectedToken(t.to_string())))
         }
     }
 
+    /// Логические операции связывают слабее сравнений, `&&` сильнее `||`:
+    /// `a < b && c || d` разбирается как `((a < b) && c) || d`. Сравнения
+    /// становятся множителями, соединёнными через `&&` в слагаемые через `||`
     fn get_expression(&mut self) -> SyntaxResult<Expression> {
+        let start = self.current_token.span;
+        let mut conjunctions = vec![vec![self.get_relation()?]];
+        loop {
+            match self.current_token.token {
+                TokenGroup::Delimiters(DelimitersGroup::And) => {
+                    self.read_token();
+                    let relation = self.get_relation()?;
+                    conjunctions.last_mut().unwrap().push(relation);
+                },
+                TokenGroup::Delimiters(DelimitersGroup::Or) => {
+                    self.read_token();
+                    conjunctions.push(vec![self.get_relation()?]);
+                },
+                _ => break
+            }
+        }
+        if conjunctions.len() == 1 && conjunctions[0].len() == 1 {
+            return Ok(conjunctions.remove(0).remove(0));
+        }
+
+        let terms: Vec<Term> = conjunctions.into_iter().map(|relations| Term {
+            span: relations[0].span.to(relations[relations.len() - 1].span),
+            operations: vec![MultiplicationOperations::And; relations.len() - 1],
+            multipliers: relations.into_iter().map(relation).collect()
+        }).collect();
+        let span = start.to(self.last_span);
+        Ok(Expression {
+            operands: vec![Operand {
+                operations: vec![AdditionOperations::Or; terms.len() - 1],
+                terms,
+                span
+            }],
+            operations: Vec::new(),
+            span
+        })
+    }
+
+    fn get_relation(&mut self) -> SyntaxResult<Expression> {
         let mut operands = Vec::new();
         let mut operations = Vec::new();
 
+        let start = self.current_token.span;
         match self.get_operand() {
             Ok(operand) => operands.push(operand),
             Err(e) => return Err(e)
         }
 
-        loop {
-            match &self.current_token.token {
-                TokenGroup::Delimiters(delimiter) => match delimiter {
-                    DelimitersGroup::NotEqual =>
-                        operations.push(RelationOperations::NotEqual),
-                    DelimitersGroup::Identical =>
-                        operations.push(RelationOperations::Equal),
-                    DelimitersGroup::Less =>
-                        operations.push(RelationOperations::Less),
-                    DelimitersGroup::Greater =>
-                        operations.push(RelationOperations::Greater),
-                    DelimitersGroup::LessEqual =>
-                        operations.push(RelationOperations::LessEqual),
-                    DelimitersGroup::GreaterEqual =>
-                        operations.push(RelationOperations::GreaterEqual),
-                    _ => break
-                },
+        while let TokenGroup::Delimiters(delimiter) = &self.current_token.token {
+            match delimiter {
+                DelimitersGroup::NotEqual =>
+                    operations.push(RelationOperations::NotEqual),
+                DelimitersGroup::Identical =>
+                    operations.push(RelationOperations::Equal),
+                DelimitersGroup::Less =>
+                    operations.push(RelationOperations::Less),
+                DelimitersGroup::Greater =>
+                    operations.push(RelationOperations::Greater),
+                DelimitersGroup::LessEqual =>
+                    operations.push(RelationOperations::LessEqual),
+                DelimitersGroup::GreaterEqual =>
+                    operations.push(RelationOperations::GreaterEqual),
                 _ => break
             }
             self.read_token();
@@ -482,33 +1027,29 @@ impl Syntax {
                 Err(e) => return Err(e)
             }
         }
-        return Ok(Expression {
+        Ok(Expression {
             operands,
-            operations
-        });
-        
+            operations,
+            span: start.to(self.last_span)
+        })
     }
 
     fn get_operand(&mut self) -> SyntaxResult<Operand> {
         let mut terms = Vec::new();
         let mut operations = Vec::new();
 
+        let start = self.current_token.span;
         match self.get_term() {
             Ok(operand) => terms.push(operand),
             Err(e) => return Err(e)
         }
 
-        loop {
-            match &self.current_token.token {
-                TokenGroup::Delimiters(delimiter) => match delimiter {
-                    DelimitersGroup::Plus =>
-                        operations.push(AdditionOperations::Addition),
-                    DelimitersGroup::Minus =>
-                        operations.push(AdditionOperations::Subtraction),
-                    DelimitersGroup::Or =>
-                        operations.push(AdditionOperations::Or),
-                    _ => break
-                },
+        while let TokenGroup::Delimiters(delimiter) = &self.current_token.token {
+            match delimiter {
+                DelimitersGroup::Plus =>
+                    operations.push(AdditionOperations::Addition),
+                DelimitersGroup::Minus =>
+                    operations.push(AdditionOperations::Subtraction),
                 _ => break
             }
             self.read_token();
@@ -517,62 +1058,130 @@ impl Syntax {
                 Err(e) => return Err(e)
             }
         }
-        return Ok(Operand {
+        Ok(Operand {
             terms,
-            operations
-        });
+            operations,
+            span: start.to(self.last_span)
+        })
     }
 
     fn get_term(&mut self) -> SyntaxResult<Term> {
         let mut multipliers = Vec::new();
         let mut operations = Vec::new();
 
-        match self.get_multiplier() {
+        let start = self.current_token.span;
+        match self.get_power() {
             Ok(multiplier) => multipliers.push(multiplier),
             Err(e) => return Err(e)
         }
 
         loop {
             match &self.current_token.token {
-                TokenGroup::Delimiters(delimiter) => match delimiter {
-                    DelimitersGroup::Asterisk =>
-                        operations.push(MultiplicationOperations::Multiplication),
-                    DelimitersGroup::Slash =>
-                        operations.push(MultiplicationOperations::Division),
-                    DelimitersGroup::And =>
-                        operations.push(MultiplicationOperations::And),
-                    _ => break
-                },
+                TokenGroup::Delimiters(DelimitersGroup::Asterisk) =>
+                    operations.push(MultiplicationOperations::Multiplication),
+                TokenGroup::Delimiters(DelimitersGroup::Slash) =>
+                    operations.push(MultiplicationOperations::Division),
+                TokenGroup::Keywords(KeywordsGroup::Mod) =>
+                    operations.push(MultiplicationOperations::Remainder),
+                TokenGroup::Keywords(KeywordsGroup::Div) =>
+                    operations.push(MultiplicationOperations::IntegerDivision),
                 _ => break
             }
             self.read_token();
-            match self.get_multiplier() {
+            match self.get_power() {
                 Ok(multiplier) =>
                     multipliers.push(multiplier),
                 Err(e) => return Err(e)
             }
         }
-        return Ok(Term {
+        Ok(Term {
             multipliers,
-            operations
-        });
+            operations,
+            span: start.to(self.last_span)
+        })
+    }
+
+    /// Множитель со степенью: `a ** b ** c` разбирается как `a ** (b ** c)`
+    fn get_power(&mut self) -> SyntaxResult<Multiplier> {
+        let base = self.get_multiplier()?;
+        if self.current_token.token != TokenGroup::Delimiters(DelimitersGroup::Power) {
+            return Ok(base);
+        }
+        self.read_token();
+        let exponent = self.get_power()?;
+        let span = base.span().to(exponent.span());
+        Ok(Multiplier::Power(Box::new(base), Box::new(exponent), span))
     }
 
     fn get_multiplier(&mut self) -> SyntaxResult<Multiplier> {
+        let span = self.current_token.span;
         let res = match &self.current_token.token {
+            TokenGroup::Identifier(id) if self.functions.contains(id) &&
+                self.next_token().token == TokenGroup::Delimiters(DelimitersGroup::LeftParenthesis) => {
+                let ident = Ident { id: *id, span };
+                self.read_token();
+                self.get_arguments().map(|arguments|
+                    Multiplier::Call(ident, arguments, span.to(self.current_token.span))
+                )
+            },
+            TokenGroup::Identifier(id) if
+                self.next_token().token == TokenGroup::Delimiters(DelimitersGroup::LeftSquareBracket) => {
+                let ident = Ident { id: *id, span };
+                self.read_token();
+                self.get_index().map(|index|
+                    Multiplier::Index(ident, Box::new(index), span.to(self.current_token.span))
+                )
+            },
             TokenGroup::Identifier(id) =>
-                Ok(Multiplier::Identifier(*id)),
+                Ok(Multiplier::Identifier(Ident { id: *id, span })),
             TokenGroup::Variables(id) =>
-                Ok(Multiplier::Variable(*id)),
+                Ok(Multiplier::Variable(*id, span)),
             TokenGroup::Keywords(KeywordsGroup::True) =>
-                Ok(Multiplier::Boolean(true)),
+                Ok(Multiplier::Boolean(true, span)),
             TokenGroup::Keywords(KeywordsGroup::False) =>
-                Ok(Multiplier::Boolean(false)),
+                Ok(Multiplier::Boolean(false, span)),
             TokenGroup::Delimiters(DelimitersGroup::Not) => {
                 self.read_token();
                 match self.get_multiplier() {
-                    Ok(multiplier) =>
-                        return Ok(Multiplier::Not(Box::new(multiplier))),
+                    Ok(multiplier) => {
+                        let span = span.to(multiplier.span());
+                        return Ok(Multiplier::Not(Box::new(multiplier), span))
+                    },
+                    Err(e) => Err(e)
+                }
+            },
+            TokenGroup::Keywords(KeywordsGroup::Real)|
+            TokenGroup::Keywords(KeywordsGroup::Integer) => {
+                let target = match self.current_token.token {
+                    TokenGroup::Keywords(KeywordsGroup::Real) => ProgramTypes::Float(None),
+                    _ => ProgramTypes::Integer(None)
+                };
+                self.read_token();
+                match self.current_token.token {
+                    TokenGroup::Delimiters(DelimitersGroup::LeftParenthesis) => self.read_token(),
+                    _ => return Err(SyntaxError::Missing(
+                        self.current_token.clone(),
+                        Message::ExpectedLeftParenthesis
+                    ))
+                }
+                let expression = self.get_expression()?;
+                match self.current_token.token {
+                    TokenGroup::Delimiters(DelimitersGroup::RightParenthesis) =>
+                        Ok(Multiplier::Convert(target, expression, span.to(self.current_token.span))),
+                    _ => Err(SyntaxError::Missing(
+                        self.current_token.clone(),
+                        Message::ExpectedRightParenthesis
+                    ))
+                }
+            },
+            // степень связывает сильнее унарного минуса: -2 ** 2 == -4
+            TokenGroup::Delimiters(DelimitersGroup::Minus) => {
+                self.read_token();
+                match self.get_power() {
+                    Ok(multiplier) => {
+                        let span = span.to(multiplier.span());
+                        return Ok(Multiplier::Neg(Box::new(multiplier), span))
+                    },
                     Err(e) => Err(e)
                 }
             },
@@ -585,7 +1194,7 @@ impl Syntax {
                         },
                         _ => Err(SyntaxError::Missing(
                             self.current_token.clone(),
-                            "Ожидалась ')'".to_string()
+                            Message::ExpectedRightParenthesis
                         ))
                     },
                     Err(e) => Err(e)
@@ -593,7 +1202,7 @@ impl Syntax {
             }
             _ => Err(SyntaxError::Missing(
                 self.current_token.clone(),
-                "Ожидался операнд".to_string()
+                Message::ExpectedOperand
             ))
         };
         if res.is_ok() {
@@ -604,31 +1213,32 @@ impl Syntax {
     }
 
     fn read_token(&mut self) {
+        self.last_span = self.current_token.span;
         if let Some(next) = self.tokens.get(self.position) {
             self.current_token = next.clone();
             self.position += 1;
         } else {
-            self.current_token = self.tokens.last().unwrap_or_else(|| {
-                &Token {
-                    token: TokenGroup::Eof,
-                    line: 0,
-                    column: 0
-                }
-            }).clone();
+            self.current_token = self.tokens.last().unwrap_or(&Token::eof()).clone();
             self.position += 1;
         }
     }
 
-    fn next_token(&mut self) -> Token {
+    fn next_token(&self) -> Token {
         match self.tokens.get(self.position) {
             Some(v) => v.clone(),
-            None => self.tokens.last().unwrap_or_else(|| {
-                &Token {
-                    token: TokenGroup::Eof,
-                    line: 0,
-                    column: 0
-                }
-            }).clone()
+            None => self.tokens.last().unwrap_or(&Token::eof()).clone()
         }
     }
-}
\ No newline at end of file
+}
+
+/// Сравнение как операнд логической операции. Выражение из одного
+/// множителя заменяется им самим, без лишних скобок
+fn relation(mut expression: Expression) -> Multiplier {
+    let single = expression.operations.is_empty()
+        && expression.operands[0].operations.is_empty()
+        && expression.operands[0].terms[0].operations.is_empty();
+    match single {
+        true => expression.operands.remove(0).terms.remove(0).multipliers.remove(0),
+        false => Multiplier::Expression(expression)
+    }
+}